@@ -1,12 +1,12 @@
+use crate::Result;
 use crate::bin_table::Row;
+use crate::error::Error;
 use crate::header::{Header, TableColumnFormat};
 use alloc::string::ToString;
 use alloc::vec;
 #[cfg(feature = "rayon")]
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use std::error::Error;
-use std::format;
-use std::prelude::rust_2015::{Box, String, Vec};
+use std::prelude::rust_2015::{String, Vec};
 
 #[derive(Debug, Clone, Default)]
 pub struct BinTable {
@@ -26,18 +26,16 @@ impl BinTable {
         }
     }
 
-    pub fn from_u8(header: &Header, data: Vec<u8>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    pub fn from_u8(header: &Header, data: Vec<u8>) -> Result<Self> {
         if header.naxis() == 2 {
-            let bytes_per_row = header.naxis_n(0).unwrap() as usize;
-            let rows = header.naxis_n(1).unwrap() as usize;
+            let bytes_per_row = header.naxis_n(0).unwrap_or(0) as usize;
+            let rows = header.naxis_n(1).unwrap_or(0) as usize;
 
             if data.len() < rows * bytes_per_row {
-                return Err(format!(
-                    "Data vec is too short, expected {} bytes, but data was only {} bytes long",
-                    rows * bytes_per_row,
-                    data.len()
-                )
-                .into());
+                return Err(Error::TruncatedData {
+                    expected: rows * bytes_per_row,
+                    actual: data.len(),
+                });
             }
 
             let field_definitions = Self::get_table_column_formats(&header)?;
@@ -48,7 +46,9 @@ impl BinTable {
                 rows,
             })
         } else {
-            Err("Only two dimensions are supported.".into())
+            Err(Error::TableFormat(
+                "Only two dimensions are supported.".into(),
+            ))
         }
     }
 
@@ -75,7 +75,7 @@ impl BinTable {
 
     fn get_table_column_formats(
         header: &Header,
-    ) -> Result<Vec<(TableColumnFormat, usize, String)>, Box<dyn Error + Send + Sync>> {
+    ) -> Result<Vec<(TableColumnFormat, usize, String)>> {
         let table_fields = header.table_fields().unwrap() as usize;
         let mut field_offset = 0;
 
@@ -87,9 +87,9 @@ impl BinTable {
                 let offset = field_offset;
                 field_offset += field_form.bytes_len();
 
-                Ok::<_, Box<dyn Error + Send + Sync>>((field_form, offset, field_type.to_string()))
+                Ok::<_, Error>((field_form, offset, field_type.to_string()))
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<Result<Vec<_>>>()?;
 
         Ok(field_definitions)
     }
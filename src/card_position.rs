@@ -0,0 +1,16 @@
+use core::fmt::{Display, Formatter};
+
+/// Where a card was found in a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CardPosition {
+    /// Index of the HDU, the primary HDU is 0
+    pub hdu: usize,
+    /// Index of the 80 character record within the header, every CONTINUE record counts
+    pub card: usize,
+}
+
+impl Display for CardPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "HDU {}, card {}", self.hdu, self.card)
+    }
+}
@@ -1,12 +1,86 @@
+use crate::card_position::CardPosition;
+use crate::parse_warning::ParseWarning;
+use alloc::format;
 use alloc::string::String;
+#[cfg(feature = "serde")]
 use alloc::string::ToString;
+#[cfg(feature = "serde")]
 use core::fmt::Display;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum Error {
+    /// Reading or writing a file failed
+    #[cfg(feature = "std")]
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The data does not start with a FITS header
+    #[error("Not a FITS file: {0}")]
+    NotFits(String),
+
+    /// A header record could not be parsed. The position is known when the card was read from a
+    /// file or a header text.
+    #[error("Invalid card {keyword}{}: {message}", at_position(position))]
+    InvalidCard {
+        keyword: String,
+        message: String,
+        position: Option<CardPosition>,
+    },
+
+    /// A problem found while reading a file in strict mode, see [`crate::OpenOptions`]
+    #[error("{0}")]
+    InvalidHeader(ParseWarning),
+
+    /// A keyword that the FITS standard requires is missing from a header
+    #[error("HDU {hdu}: Mandatory keyword {keyword} is missing")]
+    MissingKeyword { hdu: usize, keyword: String },
+
+    /// A keyword was looked up that is not in the header
+    #[error("Keyword {0} not found")]
+    KeywordNotFound(String),
+
+    /// A keyword can not be used or changed the way it was requested
+    #[error("Invalid keyword {keyword}: {message}")]
+    InvalidKeyword { keyword: String, message: String },
+
+    /// The value of a keyword has the wrong type or is out of range
+    #[error("Invalid value for {keyword}: {message}")]
+    InvalidValue { keyword: String, message: String },
+
+    #[error("Unsupported BITPIX value: {0}")]
+    UnsupportedBitpix(i64),
+
+    #[error("Unsupported extension type: {0}")]
+    UnsupportedExtension(String),
+
+    /// A data unit is shorter than its header describes
+    #[error("Data unit is truncated, expected {expected} bytes but only {actual} are available")]
+    TruncatedData { expected: usize, actual: usize },
+
+    /// The columns of a table can not be read
+    #[error("Table format error: {0}")]
+    TableFormat(String),
+
+    /// A line of a header template can not be used, see [`crate::FitsSlice::from_template`]
+    #[error("Invalid template line {line}: {message}")]
+    InvalidTemplate { line: usize, message: String },
+
+    /// Image data does not match the requested dimensions or color layout
+    #[error("Invalid image: {0}")]
+    InvalidImage(String),
+
     #[error("Deserialization error: {0}")]
     DeserializationError(String),
+
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+}
+
+fn at_position(position: &Option<CardPosition>) -> String {
+    position
+        .map(|position| format!(" at {}", position))
+        .unwrap_or_default()
 }
 
 #[cfg(feature = "serde")]
@@ -18,3 +92,13 @@ impl serde::de::Error for Error {
         Self::DeserializationError(msg.to_string())
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self::SerializationError(msg.to_string())
+    }
+}
@@ -1,4 +1,6 @@
+use crate::Result;
 use crate::hdu::{AsciiTableHDU, BinTableHDU, ExtensionHDU, ImageHDU};
+use crate::util::write_hdu;
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
@@ -17,5 +19,13 @@ pub trait Fits: Debug + Clone {
     fn extension_hdus(&self) -> impl Iterator<Item = &ExtensionHDU<Self>>;
     fn extension_hdus_mut(&mut self) -> impl Iterator<Item = &mut ExtensionHDU<Self>>;
 
-    fn to_vec(&self) -> Vec<u8>;
+    /// Serializes the primary HDU and all extension HDUs into a complete FITS file
+    fn to_vec(&self) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        write_hdu(&mut output, self.primary_hdu())?;
+        for extension_hdu in self.extension_hdus() {
+            write_hdu(&mut output, extension_hdu)?;
+        }
+        Ok(output)
+    }
 }
@@ -1,14 +1,17 @@
+use crate::Result;
+use crate::error::Error;
 use crate::fits::Fits;
 use crate::hdu::ExtensionHDU;
-use crate::header::{BayerPattern, Bitpix};
-use crate::image::{Image, ImageData};
+use crate::header::{ExtensionType, Header};
+use crate::open_options::OpenOptions;
+use crate::parse_warning::ParseWarning;
 use crate::slice_ascii_table_hdu::SliceAsciiTableHDU;
 use crate::slice_bin_table_hdu::SliceBinTableHDU;
 use crate::slice_image_hdu::SliceImageHDU;
-use alloc::boxed::Box;
+use crate::template::parse_template;
 use alloc::vec;
 use alloc::vec::Vec;
-use std::error::Error;
+use log::warn;
 
 /// A Fits file created from a buffer
 #[derive(Debug, Clone)]
@@ -18,145 +21,119 @@ pub struct FitsSlice {
 }
 
 impl FitsSlice {
-    /// Creates a new empty fits file
-    pub fn from_slice(_data: &[u8]) -> Self {
-        Self {
-            primary_hdu: SliceImageHDU {},
-            extension_hdus: vec![],
+    /// Parses a complete fits file held in memory, in lenient mode. Problems found are logged, use
+    /// [`OpenOptions::open_slice`] to get them as warnings.
+    pub fn from_slice(data: &[u8]) -> Result<Self> {
+        let (fits, warnings) = Self::from_slice_with(data, &OpenOptions::new())?;
+        for warning in warnings {
+            warn!("{}", warning);
         }
+        Ok(fits)
     }
 
-    // pub(crate) fn parse_extension_hdus(data: &[u8]) -> Result<Vec<ExtensionHDU>, Box<dyn Error + Send + Sync>> {
-    //     let mut offset = 0;
-    //     let mut extension_hdus = vec![];
-    //     while offset < data.len() {
-    //         let header = Header::from_u8(data)?;
-    //         let bytes_len = header.data_block_len();
-    //         offset += header.bytes_len();
-    //         let extension_type = header.extension().ok_or_else(|| "Missing extension type".to_string())?;
-    //
-    //         match extension_type {
-    //             ExtensionType::Image => {
-    //                 if bytes_len == 0 {
-    //                     extension_hdus.push(ExtensionHDU::Image(ImageHDU::new(header, vec![])));
-    //                 } else {
-    //                     offset += bytes_len;
-    //                     let bitpix = header.bitpix();
-    //                     let bzero = header.bzero().unwrap();
-    //                     let bscale = header.bscale().unwrap();
-    //
-    //                     let width = header.naxis_n(1).unwrap();
-    //                     let height = header.naxis_n(2).unwrap();
-    //
-    //                     let bayer_pattern = header.bayer_pattern();
-    //
-    //                     let image = Self::read_image(&data, header.bytes_len(), bitpix, bscale, bzero, bayer_pattern, width as usize, height as usize)?;
-    //
-    //                     extension_hdus.push(ExtensionHDU::Image(ImageHDU::new(header, vec![image])));
-    //                 }
-    //             }
-    //             ExtensionType::BinTable => {
-    //                 extension_hdus.push(ExtensionHDU::BinTable(BinTableHDU::from_u8(header, data[offset..(bytes_len + offset)].to_vec())?));
-    //                 offset += bytes_len;
-    //             }
-    //             ExtensionType::AsciiTable => {}
-    //         }
-    //     }
-    //
-    //     Ok(extension_hdus)
-    // }
-
-    fn read_image(
+    /// Creates an empty fits file from a CFITSIO style header template, with zero filled data
+    /// units of the sizes the headers describe. See the CFITSIO documentation of `fits_parse_template`
+    /// for the template format.
+    pub fn from_template(template: &str) -> Result<Self> {
+        let mut headers = parse_template(template)?.into_iter();
+        let header = headers.next().expect("a primary header");
+        let primary_data = vec![0; header.data_bytes_len()];
+        let primary_hdu = SliceImageHDU::new(header, primary_data);
+
+        let extension_hdus = headers
+            .map(|header| {
+                let data_len = header.data_bytes_len();
+                match header.extension() {
+                    Some(ExtensionType::BinTable) => {
+                        ExtensionHDU::BinTable(SliceBinTableHDU::new(header, vec![0; data_len]))
+                    }
+                    Some(ExtensionType::AsciiTable) => ExtensionHDU::AsciiTable(
+                        SliceAsciiTableHDU::new(header, vec![b' '; data_len]),
+                    ),
+                    _ => ExtensionHDU::Image(SliceImageHDU::new(header, vec![0; data_len])),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            primary_hdu,
+            extension_hdus,
+        })
+    }
+
+    pub(crate) fn from_slice_with(
+        data: &[u8],
+        options: &OpenOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)> {
+        let mut warnings = vec![];
+        let header = Header::from_u8(data, 0, options, &mut warnings)?
+            .ok_or_else(|| Error::NotFits("Could not read primary FITS header".into()))?;
+        header.validate(0, options, &mut warnings)?;
+
+        let mut offset = header.bytes_len();
+        let primary_data = Self::data_unit(data, offset, &header)?;
+        offset += header.data_block_len();
+        let primary_hdu = SliceImageHDU::new(header, primary_data);
+
+        let extension_hdus = Self::parse_extension_hdus(data, offset, options, &mut warnings)?;
+
+        Ok((
+            Self {
+                primary_hdu,
+                extension_hdus,
+            },
+            warnings,
+        ))
+    }
+
+    fn parse_extension_hdus(
         data: &[u8],
-        offset: usize,
-        bitpix: Bitpix,
-        bscale: f64,
-        bzero: f64,
-        bayer_pattern: Option<BayerPattern>,
-        width: usize,
-        height: usize,
-    ) -> Result<Image, Box<dyn Error + Send + Sync>> {
-        let bytes = bitpix.byte_size() * width * height;
-
-        let data = &data[offset..(bytes + offset)];
-
-        match bitpix {
-            Bitpix::F64 => {
-                let data = data
-                    .as_chunks::<8>()
-                    .0
-                    .iter()
-                    .map(|i| f64::from_be_bytes(*i))
-                    .collect();
-                Ok(Image::F64(ImageData::from_data(
-                    width,
-                    height,
-                    bzero,
-                    bscale,
-                    bayer_pattern,
-                    data,
-                )?))
-            }
-            Bitpix::F32 => {
-                let data = data
-                    .as_chunks::<4>()
-                    .0
-                    .iter()
-                    .map(|i| f32::from_be_bytes(*i))
-                    .collect();
-                Ok(Image::F32(ImageData::from_data(
-                    width,
-                    height,
-                    bzero,
-                    bscale,
-                    bayer_pattern,
-                    data,
-                )?))
-            }
-            Bitpix::U8 => {
-                let data = data.to_vec();
-                Ok(Image::U8(ImageData::from_data(
-                    width,
-                    height,
-                    bzero,
-                    bscale,
-                    bayer_pattern,
-                    data,
-                )?))
-            }
-            Bitpix::I16 => {
-                let data = data
-                    .as_chunks::<2>()
-                    .0
-                    .iter()
-                    .map(|i| i16::from_be_bytes(*i))
-                    .collect();
-                Ok(Image::I16(ImageData::from_data(
-                    width,
-                    height,
-                    bzero,
-                    bscale,
-                    bayer_pattern,
-                    data,
-                )?))
-            }
-            Bitpix::I32 => {
-                let data = data
-                    .as_chunks::<4>()
-                    .0
-                    .iter()
-                    .map(|i| i32::from_be_bytes(*i))
-                    .collect();
-                Ok(Image::I32(ImageData::from_data(
-                    width,
-                    height,
-                    bzero,
-                    bscale,
-                    bayer_pattern,
-                    data,
-                )?))
-            }
+        mut offset: usize,
+        options: &OpenOptions,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Vec<ExtensionHDU<Self>>> {
+        let mut extension_hdus = vec![];
+
+        while offset < data.len() {
+            let hdu = extension_hdus.len() + 1;
+            let Some(header) = Header::from_u8(&data[offset..], hdu, options, warnings)? else {
+                break;
+            };
+            header.validate(hdu, options, warnings)?;
+            offset += header.bytes_len();
+
+            let extension_type = header.extension().ok_or_else(|| Error::MissingKeyword {
+                hdu,
+                keyword: "XTENSION".into(),
+            })?;
+            let extension_data = Self::data_unit(data, offset, &header)?;
+            offset += header.data_block_len();
+
+            extension_hdus.push(match extension_type {
+                ExtensionType::Image => {
+                    ExtensionHDU::Image(SliceImageHDU::new(header, extension_data))
+                }
+                ExtensionType::BinTable => {
+                    ExtensionHDU::BinTable(SliceBinTableHDU::new(header, extension_data))
+                }
+                ExtensionType::AsciiTable => {
+                    ExtensionHDU::AsciiTable(SliceAsciiTableHDU::new(header, extension_data))
+                }
+            });
         }
+
+        Ok(extension_hdus)
+    }
+
+    fn data_unit(data: &[u8], offset: usize, header: &Header) -> Result<Vec<u8>> {
+        let end = offset + header.data_bytes_len();
+        if end > data.len() {
+            return Err(Error::TruncatedData {
+                expected: header.data_bytes_len(),
+                actual: data.len().saturating_sub(offset),
+            });
+        }
+        Ok(data[offset..end].to_vec())
     }
 }
 
@@ -192,8 +169,4 @@ impl Fits for FitsSlice {
     fn extension_hdus_mut(&mut self) -> impl Iterator<Item = &mut ExtensionHDU<Self>> {
         self.extension_hdus.iter_mut()
     }
-
-    fn to_vec(&self) -> Vec<u8> {
-        todo!()
-    }
 }
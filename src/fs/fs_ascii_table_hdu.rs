@@ -1,30 +1,44 @@
+use crate::Result;
 use crate::bin_table::{BinTable, Row};
+use crate::fs::read_data_unit::read_data_unit;
 use crate::hdu::{AsciiTableHDU, HDU};
 use crate::header::Header;
 use futures::stream::BoxStream;
-use std::error::Error;
 use std::path::{Path, PathBuf};
-use std::prelude::rust_2015::Box;
+use std::prelude::rust_2015::Vec;
 
 #[derive(Debug, Clone)]
 pub struct FsAsciiTableHDU {
     header: Header,
-    hdu_offset: u64,
+    data_offset: u64,
     path: PathBuf,
+
+    // Table data that has been set, but not yet saved to the file
+    data: Option<Vec<u8>>,
 }
 
 impl FsAsciiTableHDU {
-    pub fn new(
-        path: &Path,
-        header: Header,
-        hdu_offset: u64,
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    pub fn new(path: &Path, header: Header, hdu_offset: u64) -> Result<Self> {
         Ok(Self {
+            data_offset: hdu_offset + header.bytes_len() as u64,
             header,
-            hdu_offset,
             path: path.to_path_buf(),
+            data: None,
         })
     }
+
+    /// Uses the given data unit until the HDU is saved
+    pub(crate) fn with_data(mut self, data: Vec<u8>) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Points this HDU at the location its data unit was saved to
+    pub(crate) fn set_location(&mut self, path: &Path, data_offset: u64) {
+        self.path = path.to_path_buf();
+        self.data_offset = data_offset;
+        self.data = None;
+    }
 }
 
 impl HDU for FsAsciiTableHDU {
@@ -35,14 +49,22 @@ impl HDU for FsAsciiTableHDU {
     fn header_mut(&mut self) -> &mut Header {
         &mut self.header
     }
+
+    fn read_raw_data(&self) -> Result<Vec<u8>> {
+        if let Some(data) = &self.data {
+            Ok(data.clone())
+        } else {
+            read_data_unit(&self.path, self.data_offset, &self.header)
+        }
+    }
 }
 
 impl AsciiTableHDU for FsAsciiTableHDU {
-    fn read_table(&self) -> Result<BinTable, Box<dyn Error + Send + Sync>> {
+    fn read_table(&self) -> Result<BinTable> {
         todo!()
     }
 
-    fn stream_table_rows(&self) -> Result<BoxStream<'_, Row<'_>>, Box<dyn Error + Send + Sync>> {
+    fn stream_table_rows(&self) -> Result<BoxStream<'_, Row<'_>>> {
         todo!()
     }
 }
@@ -1,35 +1,49 @@
+use crate::Result;
 use crate::bin_table::{BinTable, Row};
 use crate::fs::open_fits_file::open_fits_file;
+use crate::fs::read_data_unit::read_data_unit;
 use crate::hdu::{BinTableHDU, HDU};
 use crate::header::Header;
 use crate::util::read_bytes;
 use futures::stream::BoxStream;
 #[cfg(feature = "serde")]
 use serde::de::DeserializeOwned;
-use std::error::Error;
 use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
-use std::prelude::rust_2015::{Box, Vec};
+use std::prelude::rust_2015::Vec;
 
 #[derive(Debug, Clone)]
 pub struct FsBinTableHDU {
     header: Header,
-    hdu_offset: u64,
+    data_offset: u64,
     path: PathBuf,
+
+    // Table data that has been set, but not yet saved to the file
+    data: Option<Vec<u8>>,
 }
 
 impl FsBinTableHDU {
-    pub fn new(
-        path: &Path,
-        header: Header,
-        hdu_offset: u64,
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    pub fn new(path: &Path, header: Header, hdu_offset: u64) -> Result<Self> {
         Ok(Self {
+            data_offset: hdu_offset + header.bytes_len() as u64,
             header,
-            hdu_offset,
             path: path.to_path_buf(),
+            data: None,
         })
     }
+
+    /// Uses the given data unit until the HDU is saved
+    pub(crate) fn with_data(mut self, data: Vec<u8>) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Points this HDU at the location its data unit was saved to
+    pub(crate) fn set_location(&mut self, path: &Path, data_offset: u64) {
+        self.path = path.to_path_buf();
+        self.data_offset = data_offset;
+        self.data = None;
+    }
 }
 
 impl HDU for FsBinTableHDU {
@@ -40,18 +54,29 @@ impl HDU for FsBinTableHDU {
     fn header_mut(&mut self) -> &mut Header {
         &mut self.header
     }
+
+    fn read_raw_data(&self) -> Result<Vec<u8>> {
+        if let Some(data) = &self.data {
+            Ok(data.clone())
+        } else {
+            read_data_unit(&self.path, self.data_offset, &self.header)
+        }
+    }
 }
 
 impl BinTableHDU for FsBinTableHDU {
     fn table_data_bytes_len(&self) -> u64 {
-        (self.header.naxis_n(0).unwrap() * self.header.naxis_n(1).unwrap()) as u64
+        (self.header.naxis_n(0).unwrap_or(0) * self.header.naxis_n(1).unwrap_or(0)) as u64
     }
 
-    fn read_table(&self) -> Result<BinTable, Box<dyn Error + Send + Sync>> {
+    fn read_table(&self) -> Result<BinTable> {
+        if let Some(data) = &self.data {
+            let len = self.table_data_bytes_len() as usize;
+            return BinTable::from_u8(&self.header, data[..len].to_vec());
+        }
+
         let mut reader = open_fits_file(&self.path)?;
-        reader.seek(SeekFrom::Start(
-            self.hdu_offset + self.header.bytes_len() as u64,
-        ))?;
+        reader.seek(SeekFrom::Start(self.data_offset))?;
 
         let bytes = read_bytes(&mut reader, self.table_data_bytes_len());
 
@@ -59,29 +84,21 @@ impl BinTableHDU for FsBinTableHDU {
     }
 
     #[cfg(feature = "serde")]
-    fn read_rows<T: DeserializeOwned + Send + Sync>(
-        &self,
-    ) -> Result<Vec<T>, Box<dyn Error + Send + Sync>> {
+    fn read_rows<T: DeserializeOwned + Send + Sync>(&self) -> Result<Vec<T>> {
         let table = self.read_table()?;
-        Ok(crate::bin_table::from_bin_table(&table)?)
+        crate::bin_table::from_bin_table(&table)
     }
 
     #[cfg(feature = "tokio")]
-    fn stream_table_rows_raw(
-        &self,
-    ) -> Result<BoxStream<'_, Row<'_>>, Box<dyn Error + Send + Sync>> {
+    fn stream_table_rows_raw(&self) -> Result<BoxStream<'_, Row<'_>>> {
         let mut reader = open_fits_file(&self.path)?;
-        reader.seek(SeekFrom::Start(
-            self.hdu_offset + self.header.bytes_len() as u64,
-        ))?;
+        reader.seek(SeekFrom::Start(self.data_offset))?;
         todo!()
     }
 
     #[cfg(feature = "serde")]
     #[cfg(feature = "tokio")]
-    fn stream_table_rows<T: DeserializeOwned + Send + Sync>(
-        &self,
-    ) -> Result<BoxStream<'_, T>, Box<dyn Error + Send + Sync>> {
+    fn stream_table_rows<T: DeserializeOwned + Send + Sync>(&self) -> Result<BoxStream<'_, T>> {
         todo!()
     }
 }
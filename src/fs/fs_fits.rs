@@ -1,17 +1,28 @@
+use crate::Result;
+use crate::error::Error;
 use crate::fits::Fits;
 use crate::fs::fs_ascii_table_hdu::FsAsciiTableHDU;
 use crate::fs::fs_bin_table_hdu::FsBinTableHDU;
 use crate::fs::fs_image_hdu::FsImageHDU;
 use crate::fs::is_fits_file;
 use crate::fs::open_fits_file::open_fits_file;
+use crate::fs::write_fits_file::write_fits_file;
 use crate::hdu::{ExtensionHDU, HDU};
 use crate::header::{ExtensionType, Header};
+use crate::open_options::OpenOptions;
+use crate::parse_warning::ParseWarning;
+use crate::template::parse_template;
+use crate::util::padded_data;
+use alloc::format;
+#[cfg(feature = "mmap")]
+use alloc::sync::Arc;
 use alloc::vec;
-use log::{debug, info};
-use std::error::Error;
+use log::{debug, info, warn};
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
 use std::io::Seek;
 use std::path::{Path, PathBuf};
-use std::prelude::rust_2015::{Box, Vec};
+use std::prelude::rust_2015::Vec;
 
 #[derive(Debug, Clone)]
 pub struct FsFits {
@@ -21,16 +32,29 @@ pub struct FsFits {
 }
 
 impl FsFits {
-    /// Opens a new fits file
-    pub fn open(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    /// Opens a fits file in lenient mode. Problems found are logged, use [`OpenOptions::open`] to
+    /// get them as warnings.
+    pub fn open(path: &Path) -> Result<Self> {
+        let (fits, warnings) = Self::open_with(path, &OpenOptions::new())?;
+        for warning in warnings {
+            warn!("{}", warning);
+        }
+        Ok(fits)
+    }
+
+    pub(crate) fn open_with(
+        path: &Path,
+        options: &OpenOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)> {
         Self::assert_file_type(path)?;
         debug!("Opening FITS file: {:?}", path);
         let mut reader = open_fits_file(path)?;
+        let mut warnings = vec![];
 
-        let header = Header::from_reader(&mut reader)?
-            .ok_or_else(|| "Could not read primary FITS header")?;
+        let header = Header::from_reader(&mut reader, 0, options, &mut warnings)?
+            .ok_or_else(|| Error::NotFits("Could not read primary FITS header".into()))?;
         debug!("Opened primary header: {:?}", header);
-        header.validate_primary()?;
+        header.validate(0, options, &mut warnings)?;
 
         let primary_hdu = FsImageHDU::new_primary(path, header);
 
@@ -40,8 +64,9 @@ impl FsFits {
         loop {
             reader.seek(std::io::SeekFrom::Start(offset))?;
 
-            if let Some(header) = Header::from_reader(&mut reader)? {
-                header.validate_extension()?;
+            let hdu = extension_hdus.len() + 1;
+            if let Some(header) = Header::from_reader(&mut reader, hdu, options, &mut warnings)? {
+                header.validate(hdu, options, &mut warnings)?;
                 debug!("Found extension header: {:?}", header);
 
                 match header.extension().unwrap() {
@@ -66,30 +91,128 @@ impl FsFits {
             }
         }
         info!("Opened FITS file: {:?}", path);
-        Ok(Self {
-            path: path.to_path_buf(),
-            primary_hdu,
-            extension_hdus,
-        })
+        Ok((
+            Self {
+                path: path.to_path_buf(),
+                primary_hdu,
+                extension_hdus,
+            },
+            warnings,
+        ))
     }
 
     /// Opens a file asynchronously, this avoids blocking the tokio runtime
     #[cfg(feature = "tokio")]
-    pub async fn open_async(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    pub async fn open_async(path: &Path) -> Result<Self> {
         let path = path.to_path_buf();
-        tokio::task::spawn_blocking(move || Self::open(&path)).await?
+        tokio::task::spawn_blocking(move || Self::open(&path))
+            .await
+            .map_err(std::io::Error::from)?
+    }
+
+    /// Opens a fits file and maps it into memory. Image data is then read from the mapping,
+    /// without opening, seeking and copying the file on every read, and can be viewed in place
+    /// with `mapped_image` of the image HDUs. Compressed files can not be mapped.
+    ///
+    /// The file must not be truncated or modified by other programs while it is mapped. Saving
+    /// replaces the file instead of writing into it, so the returned value and its clones can be
+    /// saved safely.
+    #[cfg(feature = "mmap")]
+    pub fn open_mmap(path: &Path) -> Result<Self> {
+        if path.to_string_lossy().ends_with(".gz") {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Compressed FITS files can not be memory mapped",
+            )
+            .into());
+        }
+
+        let mut fits = Self::open(path)?;
+        let file = std::fs::File::open(path)?;
+        // SAFETY: The mapping is read only. `save` never writes into an existing file, and
+        // modifications by other programs are ruled out by the documentation above
+        let mmap = Arc::new(unsafe { Mmap::map(&file)? });
+
+        fits.primary_hdu.set_mmap(mmap.clone());
+        for extension_hdu in &mut fits.extension_hdus {
+            if let ExtensionHDU::Image(hdu) = extension_hdu {
+                hdu.set_mmap(mmap.clone());
+            }
+        }
+        Ok(fits)
     }
 
+    /// Creates a new FITS file with an empty primary HDU. Nothing is written until [`Self::save`]
+    /// is called
     pub fn new(path: &Path) -> Self {
         Self {
             path: path.to_path_buf(),
-            primary_hdu: FsImageHDU::new_primary(path, Header::default()),
+            primary_hdu: FsImageHDU::new_primary(path, Header::new_primary()),
             extension_hdus: vec![],
         }
     }
 
-    pub fn save(&self) -> () {
-        todo!();
+    /// Creates a new FITS file from a CFITSIO style header template, with zero filled data units
+    /// of the sizes the headers describe. Nothing is written until [`Self::save`] is called
+    pub fn from_template(path: &Path, template: &str) -> Result<Self> {
+        let mut headers = parse_template(template)?.into_iter();
+        let header = headers.next().expect("a primary header");
+        let primary_data = vec![0; header.data_bytes_len()];
+        let primary_hdu = FsImageHDU::new_primary(path, header).with_data(primary_data);
+
+        let mut extension_hdus = vec![];
+        for header in headers {
+            let data_len = header.data_bytes_len();
+            extension_hdus.push(match header.extension() {
+                Some(ExtensionType::BinTable) => ExtensionHDU::BinTable(
+                    FsBinTableHDU::new(path, header, 0)?.with_data(vec![0; data_len]),
+                ),
+                Some(ExtensionType::AsciiTable) => ExtensionHDU::AsciiTable(
+                    FsAsciiTableHDU::new(path, header, 0)?.with_data(vec![b' '; data_len]),
+                ),
+                _ => ExtensionHDU::Image(
+                    FsImageHDU::new_extension(path, header, 0)?.with_data(vec![0; data_len]),
+                ),
+            });
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            primary_hdu,
+            extension_hdus,
+        })
+    }
+
+    /// Writes all HDUs to the path of this FITS file, replacing the file if it already exists.
+    /// Files ending with `.gz` are compressed.
+    pub fn save(&mut self) -> Result<()> {
+        // Update the checksums in place, so the header sizes used below match what is written
+        let data = padded_data(&self.primary_hdu)?;
+        self.primary_hdu.header_mut().refresh_checksum(&data);
+        for extension_hdu in &mut self.extension_hdus {
+            let data = padded_data(extension_hdu)?;
+            extension_hdu.header_mut().refresh_checksum(&data);
+        }
+
+        let data = self.to_vec()?;
+        write_fits_file(&self.path, &data)?;
+        info!("Saved FITS file: {:?}", self.path);
+
+        let mut offset = self.primary_hdu.header().bytes_len() as u64;
+        self.primary_hdu.set_location(&self.path, offset);
+        offset += self.primary_hdu.header().data_block_len() as u64;
+
+        for extension_hdu in &mut self.extension_hdus {
+            offset += extension_hdu.header().bytes_len() as u64;
+            match extension_hdu {
+                ExtensionHDU::Image(hdu) => hdu.set_location(&self.path, offset),
+                ExtensionHDU::BinTable(hdu) => hdu.set_location(&self.path, offset),
+                ExtensionHDU::AsciiTable(hdu) => hdu.set_location(&self.path, offset),
+            }
+            offset += extension_hdu.header().data_block_len() as u64;
+        }
+
+        Ok(())
     }
 
     /// Retrieves the path this FITS file belongs to
@@ -97,11 +220,11 @@ impl FsFits {
         &self.path
     }
 
-    fn assert_file_type(path: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+    fn assert_file_type(path: &Path) -> Result<()> {
         if is_fits_file(path) {
             Ok(())
         } else {
-            Err("Invalid file type".into())
+            Err(Error::NotFits(format!("Invalid file type {:?}", path)))
         }
     }
 }
@@ -138,8 +261,4 @@ impl Fits for FsFits {
     fn extension_hdus_mut(&mut self) -> impl Iterator<Item = &mut ExtensionHDU<Self>> {
         self.extension_hdus.iter_mut()
     }
-
-    fn to_vec(&self) -> Vec<u8> {
-        todo!()
-    }
 }
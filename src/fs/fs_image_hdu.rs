@@ -1,55 +1,178 @@
+use crate::Result;
+#[cfg(feature = "mmap")]
+use crate::fs::MappedImage;
 use crate::fs::open_fits_file::open_fits_file;
+use crate::fs::read_data_unit::{read_data_unit, read_exact};
 use crate::hdu::{HDU, ImageHDU};
-use crate::header::{BayerPattern, Header, ImageType};
-use crate::image::Image;
-use crate::util::{read_bytes, read_bytes_async};
+use crate::header::{BayerPattern, Bitpix, Header, ImageType};
+use crate::image::{Image, NdArray, read_region, read_slice};
+use crate::util::{images_to_be_bytes, read_bytes, read_bytes_async};
+#[cfg(feature = "mmap")]
+use alloc::sync::Arc;
+use alloc::vec;
+use core::ops::Range;
 use futures::StreamExt;
-use std::error::Error;
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
 use std::io::{Seek, SeekFrom};
 use std::path::{Path, PathBuf};
-use std::prelude::rust_2015::Box;
+use std::prelude::rust_2015::Vec;
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct FsImageHDU {
     header: Header,
-    hdu_offset: u64,
+    data_offset: u64,
     path: PathBuf,
+
+    // Image data that has been set, but not yet saved to the file
+    data: Option<Vec<u8>>,
+
+    // The whole file, when it was opened with FsFits::open_mmap
+    #[cfg(feature = "mmap")]
+    mmap: Option<Arc<Mmap>>,
 }
 
 impl FsImageHDU {
     pub(crate) fn new_primary(path: &Path, header: Header) -> Self {
         Self {
+            data_offset: header.bytes_len() as u64,
             header,
-            hdu_offset: 0,
             path: path.to_path_buf(),
+            data: None,
+            #[cfg(feature = "mmap")]
+            mmap: None,
         }
     }
 
-    pub fn new_extension(
-        path: &Path,
-        header: Header,
-        hdu_offset: u64,
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    pub fn new_extension(path: &Path, header: Header, hdu_offset: u64) -> Result<Self> {
         Ok(Self {
+            data_offset: hdu_offset + header.bytes_len() as u64,
             header,
-            hdu_offset,
             path: path.to_path_buf(),
+            data: None,
+            #[cfg(feature = "mmap")]
+            mmap: None,
         })
     }
 
-    fn is_image_index_valid(&self, index: usize) -> bool {
-        let naxis = self.header.naxis();
-        let max_index = if naxis == 3 {
-            self.header.naxis_n(3).unwrap() - 1
+    /// Uses the given data unit until the HDU is saved
+    pub(crate) fn with_data(mut self, data: Vec<u8>) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Points this HDU at the location its data unit was saved to
+    pub(crate) fn set_location(&mut self, path: &Path, data_offset: u64) {
+        self.path = path.to_path_buf();
+        self.data_offset = data_offset;
+        self.data = None;
+        #[cfg(feature = "mmap")]
+        {
+            self.mmap = None;
+        }
+    }
+
+    /// Reads the data unit from the given mapping of the file, instead of opening the file
+    #[cfg(feature = "mmap")]
+    pub(crate) fn set_mmap(&mut self, mmap: Arc<Mmap>) {
+        self.mmap = Some(mmap);
+    }
+
+    /// A zero-copy view of image plane `index`, or None if the file is not memory mapped or the
+    /// plane does not exist
+    #[cfg(feature = "mmap")]
+    pub fn mapped_image(&self, index: usize) -> Option<MappedImage<'_>> {
+        if !self.is_image_index_valid(index) {
+            return None;
+        }
+        let size = self.image_data_size() as usize;
+        let start = size * index;
+        let bytes = self.mapped_data()?.get(start..start + size)?;
+        Some(MappedImage::new(
+            &self.header,
+            bytes,
+            self.images_width(),
+            self.images_height(),
+        ))
+    }
+
+    /// The data unit, when the file is memory mapped
+    fn mapped_data(&self) -> Option<&[u8]> {
+        #[cfg(feature = "mmap")]
+        if let Some(mmap) = &self.mmap {
+            let start = self.data_offset as usize;
+            return mmap.get(start..start + self.header.data_bytes_len());
+        }
+        None
+    }
+
+    /// Data that can be read without going through the file: data that has not been saved yet,
+    /// or the memory mapped data unit
+    fn in_memory_data(&self) -> Option<&[u8]> {
+        self.data.as_deref().or_else(|| self.mapped_data())
+    }
+
+    fn set_data(
+        &mut self,
+        bitpix: Bitpix,
+        integer_offset: bool,
+        width: u32,
+        height: u32,
+        count: usize,
+        data: Vec<u8>,
+    ) {
+        let axes = if count > 1 {
+            vec![width as u64, height as u64, count as u64]
         } else {
-            0
+            vec![width as u64, height as u64]
         };
+        let previous = self.header.bitpix();
+        self.header.set_data_layout(bitpix, &axes);
+        self.header.set_integer_offset(integer_offset, previous);
+        self.data = Some(data);
+    }
 
-        if index > max_index as usize {
-            return false;
+    /// Reads a rectangle of an image plane on a blocking thread, see [`ImageHDU::read_region`]
+    #[cfg(feature = "tokio")]
+    pub async fn read_region_async(
+        &self,
+        index: usize,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Option<Image>> {
+        self.read_decimated_region_async(index, x, y, width, height, 1)
+            .await
+    }
+
+    /// Reads every `step`th pixel of a rectangle of an image plane on a blocking thread, see
+    /// [`ImageHDU::read_decimated_region`]
+    #[cfg(feature = "tokio")]
+    pub async fn read_decimated_region_async(
+        &self,
+        index: usize,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        step: u32,
+    ) -> Result<Option<Image>> {
+        if self.in_memory_data().is_some() {
+            return self.read_decimated_region(index, x, y, width, height, step);
         }
-        true
+
+        let hdu = self.clone();
+        tokio::task::spawn_blocking(move || {
+            hdu.read_decimated_region(index, x, y, width, height, step)
+        })
+        .await
+        .map_err(std::io::Error::from)?
+    }
+
+    fn is_image_index_valid(&self, index: usize) -> bool {
+        self.header.naxis() >= 2 && index < self.image_count()
     }
 }
 
@@ -61,11 +184,19 @@ impl HDU for FsImageHDU {
     fn header_mut(&mut self) -> &mut Header {
         &mut self.header
     }
+
+    fn read_raw_data(&self) -> Result<Vec<u8>> {
+        if let Some(data) = self.in_memory_data() {
+            Ok(data.to_vec())
+        } else {
+            read_data_unit(&self.path, self.data_offset, &self.header)
+        }
+    }
 }
 
 impl ImageHDU for FsImageHDU {
     fn image_count(&self) -> usize {
-        self.header.naxis_n(2).unwrap_or(1) as usize
+        self.header.data_shape().iter().skip(2).product()
     }
 
     fn images_width(&self) -> u32 {
@@ -90,88 +221,167 @@ impl ImageHDU for FsImageHDU {
             .or_else(|| self.header.exposure_time())
     }
 
-    fn read_image(&self, index: usize) -> Result<Option<Image>, Box<dyn Error + Send + Sync>> {
+    fn read_image(&self, index: usize) -> Result<Option<Image>> {
         if !self.is_image_index_valid(index) {
             return Ok(None);
         }
 
-        let mut reader = open_fits_file(&self.path)?;
-        reader.seek(SeekFrom::Start(
-            self.hdu_offset
-                + self.header.bytes_len() as u64
-                + (self.image_data_size() * index as u64),
-        ))?;
+        let bytes = if let Some(data) = self.in_memory_data() {
+            let start = (self.image_data_size() * index as u64) as usize;
+            data[start..start + self.image_data_size() as usize].to_vec()
+        } else {
+            let mut reader = open_fits_file(&self.path)?;
+            reader.seek(SeekFrom::Start(
+                self.data_offset + (self.image_data_size() * index as u64),
+            ))?;
 
-        let bytes = read_bytes(&mut reader, self.image_data_size());
+            read_bytes(&mut reader, self.image_data_size())
+        };
 
         let image = Image::from_data_and_header(bytes, &self.header)?;
 
         Ok(Some(image))
     }
 
-    fn clear_images(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn read_decimated_region(
+        &self,
+        index: usize,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        step: u32,
+    ) -> Result<Option<Image>> {
+        if !self.is_image_index_valid(index) {
+            return Ok(None);
+        }
+
+        let image = if let Some(data) = self.in_memory_data() {
+            read_region(
+                &self.header,
+                index,
+                x,
+                y,
+                width,
+                height,
+                step,
+                |offset, len| {
+                    let start = offset as usize;
+                    Ok(data[start..start + len].to_vec())
+                },
+            )?
+        } else {
+            let mut reader = open_fits_file(&self.path)?;
+            read_region(
+                &self.header,
+                index,
+                x,
+                y,
+                width,
+                height,
+                step,
+                |offset, len| {
+                    reader.seek(SeekFrom::Start(self.data_offset + offset))?;
+                    read_exact(&mut reader, len)
+                },
+            )?
+        };
+        Ok(Some(image))
     }
 
-    fn set_raw_images_u8(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[u8]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn read_slice(&self, ranges: &[Range<usize>]) -> Result<NdArray<f64>> {
+        if let Some(data) = self.in_memory_data() {
+            return read_slice(&self.header, ranges, |offset, len| {
+                let start = offset as usize;
+                Ok(data[start..start + len].to_vec())
+            });
+        }
+
+        let mut reader = open_fits_file(&self.path)?;
+        read_slice(&self.header, ranges, |offset, len| {
+            reader.seek(SeekFrom::Start(self.data_offset + offset))?;
+            read_exact(&mut reader, len)
+        })
     }
 
-    fn set_raw_images_i16(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[i16]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn clear_images(&mut self) -> Result<()> {
+        let bitpix = self.header.bitpix();
+        self.header.set_data_layout(bitpix, &[]);
+        self.data = Some(vec![]);
+        Ok(())
     }
 
-    fn set_raw_images_i32(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[i32]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn set_raw_images_u8(&mut self, width: u32, height: u32, images: &[&[u8]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::U8, false, width, height, images.len(), data);
+        Ok(())
     }
 
-    fn set_raw_images_f32(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[f32]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn set_raw_images_i8(&mut self, width: u32, height: u32, images: &[&[i8]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::U8, true, width, height, images.len(), data);
+        Ok(())
     }
 
-    fn set_raw_images_f64(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[f64]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn set_raw_images_i16(&mut self, width: u32, height: u32, images: &[&[i16]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I16, false, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_u16(&mut self, width: u32, height: u32, images: &[&[u16]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I16, true, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_i32(&mut self, width: u32, height: u32, images: &[&[i32]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I32, false, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_u32(&mut self, width: u32, height: u32, images: &[&[u32]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I32, true, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_i64(&mut self, width: u32, height: u32, images: &[&[i64]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I64, false, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_u64(&mut self, width: u32, height: u32, images: &[&[u64]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I64, true, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_f32(&mut self, width: u32, height: u32, images: &[&[f32]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::F32, false, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_f64(&mut self, width: u32, height: u32, images: &[&[f64]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::F64, false, width, height, images.len(), data);
+        Ok(())
     }
 
     fn stream_normalised_image(
         &self,
         index: usize,
-    ) -> Result<Option<futures::stream::BoxStream<'_, (u32, u32, f64)>>, Box<dyn Error + Send + Sync>>
-    {
+    ) -> Result<Option<futures::stream::BoxStream<'_, (u32, u32, f64)>>> {
         if !self.is_image_index_valid(index) {
             return Ok(None);
         }
 
         let mut reader = open_fits_file(&self.path)?;
         reader.seek(SeekFrom::Start(
-            self.hdu_offset
-                + self.header.bytes_len() as u64
-                + (self.image_data_size() * index as u64),
+            self.data_offset + (self.image_data_size() * index as u64),
         ))?;
 
         let bytes = read_bytes_async(reader, self.image_data_size());
@@ -0,0 +1,166 @@
+use crate::Result;
+use crate::error::Error;
+use crate::header::{Bitpix, Header};
+use crate::image::Image;
+use alloc::format;
+use core::marker::PhantomData;
+
+/// A zero-copy view of an image plane in a memory mapped file, see [`crate::fs::FsFits::open_mmap`]
+#[derive(Debug, Clone, Copy)]
+pub struct MappedImage<'a> {
+    header: &'a Header,
+    bytes: &'a [u8],
+    width: u32,
+    height: u32,
+}
+
+impl<'a> MappedImage<'a> {
+    pub(crate) fn new(header: &'a Header, bytes: &'a [u8], width: u32, height: u32) -> Self {
+        Self {
+            header,
+            bytes,
+            width,
+            height,
+        }
+    }
+
+    /// Image width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Image height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn bitpix(&self) -> Bitpix {
+        self.header.bitpix()
+    }
+
+    /// The big endian bytes of the plane, as stored in the file
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The pixels in row order, converted from big endian as they are accessed. `T` must be the
+    /// type stored for BITPIX, or the unsigned respectively signed type for images that use the
+    /// BZERO convention of [`Header::has_integer_offset`].
+    pub fn pixels<T: MappedPixel>(&self) -> Result<MappedPixels<'a, T>> {
+        let bitpix = self.header.bitpix();
+        if bitpix != T::BITPIX || (T::INTEGER_OFFSET && !self.header.has_integer_offset()) {
+            return Err(Error::InvalidImage(format!(
+                "Can not view {:?} image data as {}",
+                bitpix,
+                core::any::type_name::<T>()
+            )));
+        }
+        Ok(MappedPixels {
+            bytes: self.bytes,
+            _pixel: PhantomData,
+        })
+    }
+
+    /// Copies the plane into an owned image
+    pub fn to_image(&self) -> Result<Image> {
+        Image::from_region_data(
+            self.bytes.to_vec(),
+            self.width as usize,
+            self.height as usize,
+            self.header.bayer_pattern(),
+            self.header,
+        )
+    }
+}
+
+/// Typed access to big endian pixels, without copying them
+#[derive(Debug, Clone, Copy)]
+pub struct MappedPixels<'a, T> {
+    bytes: &'a [u8],
+    _pixel: PhantomData<T>,
+}
+
+impl<'a, T: MappedPixel> MappedPixels<'a, T> {
+    pub fn len(&self) -> usize {
+        self.bytes.len() / size_of::<T>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The pixel at the given position in row order
+    pub fn get(&self, index: usize) -> Option<T> {
+        let start = index.checked_mul(size_of::<T>())?;
+        self.bytes
+            .get(start..start + size_of::<T>())
+            .map(T::from_be_slice)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + 'a {
+        self.bytes
+            .chunks_exact(size_of::<T>())
+            .map(T::from_be_slice)
+    }
+}
+
+/// Pixel types that can be read in place from big endian data
+pub trait MappedPixel: Copy + 'static {
+    /// The BITPIX of data units holding this type
+    const BITPIX: Bitpix;
+
+    /// Whether the type is stored shifted by BZERO, cf. FITSv4, section 5.2.5
+    const INTEGER_OFFSET: bool;
+
+    /// Decodes a value from exactly `size_of::<Self>()` bytes
+    fn from_be_slice(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_mapped_pixel {
+    ($($ty:ty => $bitpix:expr),*) => {
+        $(
+            impl MappedPixel for $ty {
+                const BITPIX: Bitpix = $bitpix;
+                const INTEGER_OFFSET: bool = false;
+
+                fn from_be_slice(bytes: &[u8]) -> Self {
+                    <$ty>::from_be_bytes(bytes.try_into().expect("a value sized slice"))
+                }
+            }
+        )*
+    };
+}
+
+impl_mapped_pixel!(
+    u8 => Bitpix::U8,
+    i16 => Bitpix::I16,
+    i32 => Bitpix::I32,
+    i64 => Bitpix::I64,
+    f32 => Bitpix::F32,
+    f64 => Bitpix::F64
+);
+
+/// Types stored as the integer type of the other signedness, flipping the sign bit removes the
+/// offset
+macro_rules! impl_mapped_pixel_offset {
+    ($($ty:ty => $bitpix:expr),*) => {
+        $(
+            impl MappedPixel for $ty {
+                const BITPIX: Bitpix = $bitpix;
+                const INTEGER_OFFSET: bool = true;
+
+                fn from_be_slice(bytes: &[u8]) -> Self {
+                    let stored = <$ty>::from_be_bytes(bytes.try_into().expect("a value sized slice"));
+                    stored ^ (1 << (<$ty>::BITS - 1))
+                }
+            }
+        )*
+    };
+}
+
+impl_mapped_pixel_offset!(
+    i8 => Bitpix::U8,
+    u16 => Bitpix::I16,
+    u32 => Bitpix::I32,
+    u64 => Bitpix::I64
+);
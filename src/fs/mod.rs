@@ -1,6 +1,10 @@
 mod file_progress;
 mod is_fits_file;
+#[cfg(feature = "mmap")]
+mod mapped_image;
 mod open_fits_file;
+mod read_data_unit;
+mod write_fits_file;
 
 mod fs_ascii_table_hdu;
 mod fs_bin_table_hdu;
@@ -10,3 +14,5 @@ mod fs_image_hdu;
 pub use self::file_progress::FileProgress;
 pub use self::fs_fits::FsFits;
 pub use self::is_fits_file::is_fits_file;
+#[cfg(feature = "mmap")]
+pub use self::mapped_image::{MappedImage, MappedPixel, MappedPixels};
@@ -9,7 +9,7 @@ pub fn open_fits_file(path: &Path) -> Result<Box<dyn ReadSeek>, io::Error> {
     let file = OpenOptions::new().read(true).open(&path)?;
 
     #[cfg(feature = "gzip")]
-    if path.ends_with(".gz") {
+    if path.to_string_lossy().ends_with(".gz") {
         let mut decoder = flate2::read::GzDecoder::new(file);
 
         let mut data = alloc::vec![];
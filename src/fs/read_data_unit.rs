@@ -0,0 +1,35 @@
+use crate::Result;
+use crate::error::Error;
+use crate::fs::open_fits_file::open_fits_file;
+use crate::header::Header;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::io::{Read, SeekFrom};
+use std::path::Path;
+
+/// Reads the complete data unit described by `header`, starting at `data_offset` in the file
+pub(crate) fn read_data_unit(path: &Path, data_offset: u64, header: &Header) -> Result<Vec<u8>> {
+    let bytes_len = header.data_bytes_len();
+    if bytes_len == 0 {
+        return Ok(vec![]);
+    }
+
+    let mut reader = open_fits_file(path)?;
+    reader.seek(SeekFrom::Start(data_offset))?;
+    read_exact(&mut reader, bytes_len)
+}
+
+/// Reads `len` bytes from the current position of `reader`, failing if the file ends before
+pub(crate) fn read_exact(reader: &mut impl Read, len: usize) -> Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(len);
+    reader.take(len as u64).read_to_end(&mut data)?;
+
+    if data.len() < len {
+        return Err(Error::TruncatedData {
+            expected: len,
+            actual: data.len(),
+        });
+    }
+
+    Ok(data)
+}
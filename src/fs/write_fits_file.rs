@@ -0,0 +1,44 @@
+use alloc::format;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Writes `data` to a temporary file next to `path` and then renames it over `path`. The file is
+/// replaced rather than truncated, so existing memory mappings of it stay valid.
+pub fn write_fits_file(path: &Path, data: &[u8]) -> Result<(), io::Error> {
+    let temp_path = temp_path(path);
+    let write = || -> Result<(), io::Error> {
+        let mut file = File::create(&temp_path)?;
+
+        #[cfg(feature = "gzip")]
+        if path.to_string_lossy().ends_with(".gz") {
+            let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            encoder.write_all(data)?;
+            encoder.finish()?.sync_all()
+        } else {
+            file.write_all(data)?;
+            file.sync_all()
+        }
+
+        #[cfg(not(feature = "gzip"))]
+        {
+            file.write_all(data)?;
+            file.sync_all()
+        }
+    };
+
+    let result = write().and_then(|_| std::fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}.tmp", std::process::id()));
+    path.with_file_name(name)
+}
@@ -0,0 +1,23 @@
+/// Outcome of checking one of the checksum keywords of a HDU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    Valid,
+    Invalid,
+    Missing,
+}
+
+/// Result of [`crate::hdu::HDU::verify_checksum`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChecksumVerification {
+    /// The CHECKSUM keyword, covering the header together with the data unit
+    pub header: ChecksumStatus,
+    /// The DATASUM keyword, covering only the data unit
+    pub data: ChecksumStatus,
+}
+
+impl ChecksumVerification {
+    /// Whether both keywords are present and match the contents of the HDU
+    pub fn is_valid(&self) -> bool {
+        self.header == ChecksumStatus::Valid && self.data == ChecksumStatus::Valid
+    }
+}
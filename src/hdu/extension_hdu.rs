@@ -1,4 +1,8 @@
 use crate::Fits;
+use crate::Result;
+use crate::hdu::HDU;
+use crate::header::Header;
+use alloc::vec::Vec;
 
 #[derive(Debug, Clone)]
 pub enum ExtensionHDU<F: Fits> {
@@ -6,3 +10,29 @@ pub enum ExtensionHDU<F: Fits> {
     BinTable(F::BinTableHDU),
     AsciiTable(F::AsciiTableHDU),
 }
+
+impl<F: Fits> HDU for ExtensionHDU<F> {
+    fn header(&self) -> &Header {
+        match self {
+            ExtensionHDU::Image(hdu) => hdu.header(),
+            ExtensionHDU::BinTable(hdu) => hdu.header(),
+            ExtensionHDU::AsciiTable(hdu) => hdu.header(),
+        }
+    }
+
+    fn header_mut(&mut self) -> &mut Header {
+        match self {
+            ExtensionHDU::Image(hdu) => hdu.header_mut(),
+            ExtensionHDU::BinTable(hdu) => hdu.header_mut(),
+            ExtensionHDU::AsciiTable(hdu) => hdu.header_mut(),
+        }
+    }
+
+    fn read_raw_data(&self) -> Result<Vec<u8>> {
+        match self {
+            ExtensionHDU::Image(hdu) => hdu.read_raw_data(),
+            ExtensionHDU::BinTable(hdu) => hdu.read_raw_data(),
+            ExtensionHDU::AsciiTable(hdu) => hdu.read_raw_data(),
+        }
+    }
+}
@@ -1,13 +1,26 @@
+use crate::Result;
+use crate::hdu::ChecksumVerification;
 use crate::header::Header;
+use crate::util::padded_data;
+use alloc::vec::Vec;
 
 pub trait HDU {
     fn header(&self) -> &Header;
     fn header_mut(&mut self) -> &mut Header;
 
+    /// Reads the raw big endian data unit of this HDU, without the padding of the last FITS block
+    fn read_raw_data(&self) -> Result<Vec<u8>>;
+
     /// Total size of this HDU in bytes. Including both header and data, and aligned to the Fits
     /// blocks.
     fn byte_size(&self) -> u64 {
         let header = self.header();
         (header.bytes_len() + header.data_block_len()) as u64
     }
+
+    /// Checks the CHECKSUM and DATASUM keywords against the contents of this HDU
+    fn verify_checksum(&self) -> Result<ChecksumVerification> {
+        let data = padded_data(self)?;
+        Ok(self.header().verify_checksum(&data))
+    }
 }
@@ -1,10 +1,14 @@
+use crate::Result;
+use crate::error::Error;
 use crate::hdu::hdu::HDU;
-use crate::header::{BayerPattern, ImageType};
-use crate::image::Image;
-use alloc::boxed::Box;
+use crate::header::card_keys;
+use crate::header::{BayerPattern, Bitpix, ImageType};
+use crate::image::{Image, NdArray};
+use alloc::collections::BTreeSet;
+use alloc::format;
+use core::ops::Range;
 #[cfg(feature = "image")]
 use image::{ImageBuffer, Luma, Primitive};
-use std::error::Error;
 use std::fmt;
 use std::prelude::rust_2015::Vec;
 
@@ -15,101 +19,263 @@ pub trait ImageHDU: HDU + fmt::Debug + Send + Sync {
     fn images_bayer_pattern(&self) -> Option<BayerPattern>;
     fn images_type(&self) -> Option<&ImageType>;
     fn images_exposure_time(&self) -> Option<core::time::Duration>;
-    fn read_image(&self, index: usize) -> Result<Option<Image>, Box<dyn Error + Send + Sync>>;
+    fn read_image(&self, index: usize) -> Result<Option<Image>>;
+
+    /// Reads a rectangle of image plane `index`, without loading the rest of the plane
+    fn read_region(
+        &self,
+        index: usize,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Option<Image>> {
+        self.read_decimated_region(index, x, y, width, height, 1)
+    }
+
+    /// Reads every `step`th pixel in both directions of a rectangle of image plane `index`, e.g.
+    /// for fast previews. Decimating by an even step drops the bayer pattern.
+    fn read_decimated_region(
+        &self,
+        index: usize,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        step: u32,
+    ) -> Result<Option<Image>>;
+
+    /// Reads a hyperslab of the data unit as physical values, with one range per NAXISn. BLANK
+    /// pixels are NaN.
+    fn read_slice(&self, ranges: &[Range<usize>]) -> Result<NdArray<f64>>;
 
     #[cfg(feature = "image")]
-    fn set_images_u8(
-        &mut self,
-        images: &[&ImageBuffer<Luma<u8>, Vec<u8>>],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+    fn set_images_u8(&mut self, images: &[&ImageBuffer<Luma<u8>, Vec<u8>>]) -> Result<()> {
         get_raw_data_from_image(self, images, Self::set_raw_images_u8)
     }
     #[cfg(feature = "image")]
-    fn set_images_i16(
-        &mut self,
-        images: &[&ImageBuffer<Luma<i16>, Vec<i16>>],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+    fn set_images_i8(&mut self, images: &[&ImageBuffer<Luma<i8>, Vec<i8>>]) -> Result<()> {
+        get_raw_data_from_image(self, images, Self::set_raw_images_i8)
+    }
+    #[cfg(feature = "image")]
+    fn set_images_i16(&mut self, images: &[&ImageBuffer<Luma<i16>, Vec<i16>>]) -> Result<()> {
         get_raw_data_from_image(self, images, Self::set_raw_images_i16)
     }
     #[cfg(feature = "image")]
-    fn set_images_i32(
-        &mut self,
-        images: &[&ImageBuffer<Luma<i32>, Vec<i32>>],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+    fn set_images_u16(&mut self, images: &[&ImageBuffer<Luma<u16>, Vec<u16>>]) -> Result<()> {
+        get_raw_data_from_image(self, images, Self::set_raw_images_u16)
+    }
+    #[cfg(feature = "image")]
+    fn set_images_i32(&mut self, images: &[&ImageBuffer<Luma<i32>, Vec<i32>>]) -> Result<()> {
         get_raw_data_from_image(self, images, Self::set_raw_images_i32)
     }
     #[cfg(feature = "image")]
-    fn set_images_f32(
-        &mut self,
-        images: &[&ImageBuffer<Luma<f32>, Vec<f32>>],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+    fn set_images_u32(&mut self, images: &[&ImageBuffer<Luma<u32>, Vec<u32>>]) -> Result<()> {
+        get_raw_data_from_image(self, images, Self::set_raw_images_u32)
+    }
+    #[cfg(feature = "image")]
+    fn set_images_i64(&mut self, images: &[&ImageBuffer<Luma<i64>, Vec<i64>>]) -> Result<()> {
+        get_raw_data_from_image(self, images, Self::set_raw_images_i64)
+    }
+    #[cfg(feature = "image")]
+    fn set_images_u64(&mut self, images: &[&ImageBuffer<Luma<u64>, Vec<u64>>]) -> Result<()> {
+        get_raw_data_from_image(self, images, Self::set_raw_images_u64)
+    }
+    #[cfg(feature = "image")]
+    fn set_images_f32(&mut self, images: &[&ImageBuffer<Luma<f32>, Vec<f32>>]) -> Result<()> {
         get_raw_data_from_image(self, images, Self::set_raw_images_f32)
     }
     #[cfg(feature = "image")]
-    fn set_images_f64(
-        &mut self,
-        images: &[&ImageBuffer<Luma<f64>, Vec<f64>>],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+    fn set_images_f64(&mut self, images: &[&ImageBuffer<Luma<f64>, Vec<f64>>]) -> Result<()> {
         get_raw_data_from_image(self, images, Self::set_raw_images_f64)
     }
 
-    fn clear_images(&mut self) -> Result<(), Box<dyn Error + Send + Sync>>;
+    fn clear_images(&mut self) -> Result<()>;
 
-    fn set_raw_images_u8(
-        &mut self,
-        width: u32,
-        height: u32,
-        images: &[&[u8]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>>;
-    fn set_raw_images_i16(
-        &mut self,
-        width: u32,
-        height: u32,
-        images: &[&[i16]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>>;
-    fn set_raw_images_i32(
-        &mut self,
-        width: u32,
-        height: u32,
-        images: &[&[i32]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>>;
-    fn set_raw_images_f32(
-        &mut self,
-        width: u32,
-        height: u32,
-        images: &[&[f32]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>>;
-    fn set_raw_images_f64(
+    /// Stores physical pixel values with the given BITPIX, converting them back to raw values with
+    /// the BZERO and BSCALE of the header. For integer BITPIX, NaN pixels are stored as BLANK. When
+    /// the header has none, BLANK is set to a value no pixel uses, preferring the smallest value of
+    /// the type. Pixels that would round to BLANK are moved by one so they don't read back as null.
+    fn set_physical_images(
         &mut self,
+        bitpix: Bitpix,
         width: u32,
         height: u32,
         images: &[&[f64]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+    ) -> Result<()> {
+        let zero = self.header().bzero().unwrap_or(0.0);
+        let scale = self.header().bscale().unwrap_or(1.0);
+        let blank = self.header().blank();
+        let to_raw = |value: &f64| (value - zero) / scale;
+
+        let blank = match bitpix {
+            Bitpix::F64 => {
+                let raw = convert(images, |value| to_raw(value));
+                self.set_raw_images_f64(width, height, &slices(&raw))?;
+                None
+            }
+            Bitpix::F32 => {
+                let raw = convert(images, |value| to_raw(value) as f32);
+                self.set_raw_images_f32(width, height, &slices(&raw))?;
+                None
+            }
+            Bitpix::U8 => {
+                let (raw, blank) =
+                    quantize::<u8>(images, blank, (u8::MIN.into(), u8::MAX.into()), to_raw)?;
+                self.set_raw_images_u8(width, height, &slices(&raw))?;
+                blank
+            }
+            Bitpix::I16 => {
+                let (raw, blank) =
+                    quantize::<i16>(images, blank, (i16::MIN.into(), i16::MAX.into()), to_raw)?;
+                self.set_raw_images_i16(width, height, &slices(&raw))?;
+                blank
+            }
+            Bitpix::I32 => {
+                let (raw, blank) =
+                    quantize::<i32>(images, blank, (i32::MIN.into(), i32::MAX.into()), to_raw)?;
+                self.set_raw_images_i32(width, height, &slices(&raw))?;
+                blank
+            }
+            Bitpix::I64 => {
+                let (raw, blank) = quantize::<i64>(images, blank, (i64::MIN, i64::MAX), to_raw)?;
+                self.set_raw_images_i64(width, height, &slices(&raw))?;
+                blank
+            }
+        };
+
+        // The raw setters drop the integer offset conventions, restore what the values used
+        let header = self.header_mut();
+        if zero != 0.0 || scale != 1.0 {
+            header.set_bzero(zero);
+            header.set_bscale(scale);
+        }
+        if let Some(blank) = blank {
+            header.set_blank(blank);
+        }
+        Ok(())
+    }
+
+    fn set_raw_images_u8(&mut self, width: u32, height: u32, images: &[&[u8]]) -> Result<()>;
+    fn set_raw_images_i8(&mut self, width: u32, height: u32, images: &[&[i8]]) -> Result<()>;
+    fn set_raw_images_i16(&mut self, width: u32, height: u32, images: &[&[i16]]) -> Result<()>;
+    fn set_raw_images_u16(&mut self, width: u32, height: u32, images: &[&[u16]]) -> Result<()>;
+    fn set_raw_images_i32(&mut self, width: u32, height: u32, images: &[&[i32]]) -> Result<()>;
+    fn set_raw_images_u32(&mut self, width: u32, height: u32, images: &[&[u32]]) -> Result<()>;
+    fn set_raw_images_i64(&mut self, width: u32, height: u32, images: &[&[i64]]) -> Result<()>;
+    fn set_raw_images_u64(&mut self, width: u32, height: u32, images: &[&[u64]]) -> Result<()>;
+    fn set_raw_images_f32(&mut self, width: u32, height: u32, images: &[&[f32]]) -> Result<()>;
+    fn set_raw_images_f64(&mut self, width: u32, height: u32, images: &[&[f64]]) -> Result<()>;
 
     #[cfg(feature = "tokio")]
     fn stream_normalised_image(
         &self,
         index: usize,
-    ) -> Result<Option<futures::stream::BoxStream<'_, (u32, u32, f64)>>, Box<dyn Error + Send + Sync>>;
+    ) -> Result<Option<futures::stream::BoxStream<'_, (u32, u32, f64)>>>;
     fn image_data_size(&self) -> u64;
 }
 
+fn convert<T>(images: &[&[f64]], to_raw: impl Fn(&f64) -> T) -> Vec<Vec<T>> {
+    images
+        .iter()
+        .map(|image| image.iter().map(&to_raw).collect())
+        .collect()
+}
+
+/// Rounds raw values to an integer type with the range `min..=max`, returning BLANK if any
+/// pixel is NaN
+fn quantize<T: TryFrom<i64>>(
+    images: &[&[f64]],
+    blank: Option<i64>,
+    (min, max): (i64, i64),
+    to_raw: impl Fn(&f64) -> f64,
+) -> Result<(Vec<Vec<T>>, Option<i64>)> {
+    let rounded = convert(images, |value| {
+        (!value.is_nan()).then(|| to_raw(value).round().clamp(min as f64, max as f64) as i64)
+    });
+    let has_nulls = rounded.iter().flatten().any(Option::is_none);
+    let blank = match blank {
+        Some(blank) if !(min..=max).contains(&blank) => {
+            return Err(Error::InvalidValue {
+                keyword: card_keys::BLANK.into(),
+                message: format!("{blank} is outside of the pixel range {min} to {max}"),
+            });
+        }
+        Some(blank) => Some(blank),
+        None if has_nulls => Some(unused_value(&rounded, min, max)),
+        None => None,
+    };
+
+    let raw = rounded
+        .iter()
+        .map(|image| {
+            image
+                .iter()
+                .map(|value| {
+                    let value = match *value {
+                        None => blank.unwrap_or(min),
+                        Some(value) if Some(value) == blank && value < max => value + 1,
+                        Some(value) if Some(value) == blank => value - 1,
+                        Some(value) => value,
+                    };
+                    T::try_from(value)
+                        .unwrap_or_else(|_| unreachable!("values are clamped to the pixel range"))
+                })
+                .collect()
+        })
+        .collect();
+    Ok((raw, has_nulls.then_some(blank).flatten()))
+}
+
+/// Finds a value in `min..=max` that no pixel uses, trying the ends of the range first
+fn unused_value(images: &[Vec<Option<i64>>], min: i64, max: i64) -> i64 {
+    let is_used = |candidate| {
+        images
+            .iter()
+            .flatten()
+            .any(|value| *value == Some(candidate))
+    };
+    if !is_used(min) {
+        return min;
+    }
+    if !is_used(max) {
+        return max;
+    }
+    let used = images.iter().flatten().flatten().collect::<BTreeSet<_>>();
+    (min..=max)
+        .find(|value| !used.contains(value))
+        .unwrap_or(min)
+}
+
+fn slices<T>(images: &[Vec<T>]) -> Vec<&[T]> {
+    images.iter().map(Vec::as_slice).collect()
+}
+
+#[cfg(feature = "image")]
 fn get_raw_data_from_image<
     'a,
     T: Primitive,
     S: ImageHDU + ?Sized,
-    CB: FnOnce(&mut S, u32, u32, &[&[T]]) -> Result<(), Box<dyn Error + Send + Sync>>,
+    CB: FnOnce(&mut S, u32, u32, &[&[T]]) -> Result<()>,
 >(
     hdu: &mut S,
     images: &'a [&'a ImageBuffer<Luma<T>, Vec<T>>],
     callback: CB,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
+) -> Result<()> {
     if images.is_empty() {
         hdu.clear_images()?;
         Ok(())
     } else {
-        let width = images[0].width();
-        let height = images[0].width();
+        let (width, height) = images[0].dimensions();
+        if images
+            .iter()
+            .any(|image| image.dimensions() != (width, height))
+        {
+            return Err(Error::InvalidImage(format!(
+                "All images must be {}x{} pixels, like the first one",
+                width, height
+            )));
+        }
 
         let data = images
             .into_iter()
@@ -1,11 +1,13 @@
 mod ascii_table_hdu;
 mod bin_table_hdu;
+mod checksum;
 mod extension_hdu;
 mod hdu;
 mod image_hdu;
 
 pub use self::ascii_table_hdu::AsciiTableHDU;
 pub use self::bin_table_hdu::BinTableHDU;
+pub use self::checksum::{ChecksumStatus, ChecksumVerification};
 pub use self::extension_hdu::ExtensionHDU;
 pub use self::hdu::HDU;
 pub use self::image_hdu::ImageHDU;
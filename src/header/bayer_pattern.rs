@@ -1,8 +1,6 @@
-use alloc::boxed::Box;
 use alloc::format;
 use alloc::string::String;
 use alloc::string::ToString;
-use core::error::Error;
 
 /// The camera bayer pattern
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -13,6 +11,32 @@ pub enum BayerPattern {
     GBRG,
 }
 
+impl BayerPattern {
+    /// The pattern of a region that starts at the given pixel
+    pub(crate) fn shifted(self, x: u32, y: u32) -> Self {
+        let pattern = if x % 2 == 1 {
+            match self {
+                BayerPattern::RGGB => BayerPattern::GRBG,
+                BayerPattern::GRBG => BayerPattern::RGGB,
+                BayerPattern::BGGR => BayerPattern::GBRG,
+                BayerPattern::GBRG => BayerPattern::BGGR,
+            }
+        } else {
+            self
+        };
+        if y % 2 == 1 {
+            match pattern {
+                BayerPattern::RGGB => BayerPattern::GBRG,
+                BayerPattern::GBRG => BayerPattern::RGGB,
+                BayerPattern::BGGR => BayerPattern::GRBG,
+                BayerPattern::GRBG => BayerPattern::BGGR,
+            }
+        } else {
+            pattern
+        }
+    }
+}
+
 impl From<BayerPattern> for String {
     fn from(pattern: BayerPattern) -> Self {
         match pattern {
@@ -25,7 +49,7 @@ impl From<BayerPattern> for String {
 }
 
 impl TryFrom<String> for BayerPattern {
-    type Error = Box<dyn Error + Send + Sync>;
+    type Error = crate::Error;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
         match value.to_lowercase().as_str() {
@@ -33,7 +57,10 @@ impl TryFrom<String> for BayerPattern {
             "bggr" => Ok(BayerPattern::BGGR),
             "grbg" => Ok(BayerPattern::GRBG),
             "gbrg" => Ok(BayerPattern::GBRG),
-            _ => Err(From::from(format!("Invalid BAYERPAT value: {}", value))),
+            _ => Err(crate::Error::InvalidValue {
+                keyword: "BAYERPAT".into(),
+                message: format!("Unknown bayer pattern '{}'", value),
+            }),
         }
     }
 }
@@ -1,7 +1,3 @@
-use alloc::boxed::Box;
-use alloc::format;
-use core::error::Error;
-
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Bitpix {
     /// Float 64
@@ -18,6 +14,9 @@ pub enum Bitpix {
 
     /// Signed 32 bit
     I32 = 32,
+
+    /// Signed 64 bit
+    I64 = 64,
 }
 
 impl Bitpix {
@@ -28,6 +27,19 @@ impl Bitpix {
             Bitpix::U8 => 1,
             Bitpix::I16 => 2,
             Bitpix::I32 => 4,
+            Bitpix::I64 => 8,
+        }
+    }
+
+    /// The BZERO value that stores the other signedness of this integer type, e.g. unsigned 16 bit
+    /// integers as signed ones, cf. FITSv4, section 5.2.5
+    pub fn integer_offset(&self) -> Option<f64> {
+        match self {
+            Bitpix::F64 | Bitpix::F32 => None,
+            Bitpix::U8 => Some(-128.0),
+            Bitpix::I16 => Some(32768.0),
+            Bitpix::I32 => Some(2147483648.0),
+            Bitpix::I64 => Some(9223372036854775808.0),
         }
     }
 }
@@ -39,7 +51,7 @@ impl From<Bitpix> for i64 {
 }
 
 impl TryFrom<i64> for Bitpix {
-    type Error = Box<dyn Error + Send + Sync>;
+    type Error = crate::Error;
 
     fn try_from(value: i64) -> Result<Self, Self::Error> {
         match value {
@@ -48,7 +60,8 @@ impl TryFrom<i64> for Bitpix {
             8 => Ok(Bitpix::U8),
             16 => Ok(Bitpix::I16),
             32 => Ok(Bitpix::I32),
-            _ => Err(From::from(format!("Invalid bitpix value: {}", value))),
+            64 => Ok(Bitpix::I64),
+            _ => Err(crate::Error::UnsupportedBitpix(value)),
         }
     }
 }
@@ -1,13 +1,14 @@
+use crate::Result;
+use crate::error::Error;
 use crate::header::extension_type::ExtensionType;
 use crate::header::value::Value;
 use crate::header::{BayerPattern, Bitpix, ImageType, TableColumnFormat, card_keys};
-use alloc::boxed::Box;
 use alloc::format;
 use alloc::string::String;
 use alloc::string::ToString;
+use alloc::vec;
 use alloc::vec::Vec;
 use chrono::{DateTime, NaiveDateTime, Utc};
-use core::error::Error;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Card {
@@ -337,175 +338,219 @@ pub enum Card {
 }
 
 impl Card {
-    pub fn try_from(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let key_word = std::str::from_utf8(buf[..8].trim_ascii())?;
+    pub fn try_from(buf: &[u8; 80]) -> Result<Self> {
+        let key_word =
+            core::str::from_utf8(buf[..8].trim_ascii()).map_err(|err| Error::InvalidCard {
+                keyword: String::from_utf8_lossy(&buf[..8]).trim_ascii().into(),
+                message: err.to_string(),
+                position: None,
+            })?;
+        Self::parse_record(key_word, buf).map_err(|err| with_keyword(err, key_word))
+    }
+
+    fn parse_record(key_word: &str, buf: &[u8; 80]) -> Result<Self> {
         match key_word {
             "" => parse_empty_keyword_card(buf),
-            card_keys::AUTHOR => Ok(Self::parse_author(buf)?),
-            card_keys::BITPIX => Ok(Self::parse_bitpix(buf)?),
-            card_keys::BLANK => Ok(Self::parse_blank(buf)?),
-            card_keys::BLOCKED => Ok(Self::parse_blocked(buf)?),
-            card_keys::BSCALE => Ok(Self::parse_bscale(buf)?),
-            card_keys::BUNIT => Ok(Self::parse_bunit(buf)?),
-            card_keys::BZERO => Ok(Self::parse_bzero(buf)?),
-            card_keys::DATAMAX => Ok(Self::parse_data_max(buf)?),
-            card_keys::DATAMIN => Ok(Self::parse_data_min(buf)?),
-            card_keys::DATE => Ok(Self::parse_date(buf)?),
-            card_keys::DATE_OBS => Ok(Self::parse_date_observed(buf)?),
-            card_keys::EPOCH => Ok(Self::parse_epoch(buf)?),
-            card_keys::EQUINOX => Ok(Self::parse_equinox(buf)?),
-            card_keys::EXTEND => Ok(Self::parse_extend(buf)?),
-            card_keys::EXTLEVEL => Ok(Self::parse_extension_level(buf)?),
-            card_keys::EXTNAME => Ok(Self::parse_extension_name(buf)?),
-            card_keys::EXTVER => Ok(Self::parse_extension_version(buf)?),
-            card_keys::GCOUNT => Ok(Self::parse_group_count(buf)?),
-            card_keys::GROUPS => Ok(Self::parse_groups(buf)?),
-            card_keys::INSTRUME => Ok(Self::parse_instrument(buf)?),
-            card_keys::NAXIS => Ok(Self::parse_naxis(buf)?),
-            card_keys::OBJECT => Ok(Self::parse_object(buf)?),
-            card_keys::OBSERVER => Ok(Self::parse_observer(buf)?),
-            card_keys::ORIGIN => Ok(Self::parse_origin(buf)?),
-            card_keys::PCOUNT => Ok(Self::parse_pcount(buf)?),
-            card_keys::REFERENC => Ok(Self::parse_reference(buf)?),
-            card_keys::SIMPLE => Ok(Self::parse_simple(buf)?),
-            card_keys::TELESCOP => Ok(Self::parse_telescope(buf)?),
-            card_keys::TFIELDS => Ok(Self::parse_table_fields(buf)?),
-            card_keys::THEAP => Ok(Self::parse_table_heap(buf)?),
-            card_keys::XTENSION => Ok(Self::parse_xtension(buf)?),
-            card_keys::FOCALLEN => Ok(Self::parse_focal_length(buf)?),
-            card_keys::EXPTIME => Ok(Self::parse_exposure_time(buf)?),
-            card_keys::CCD_TEMP => Ok(Self::parse_ccd_temperature(buf)?),
-            card_keys::BAYERPAT => Ok(Self::parse_bayer_pattern(buf)?),
-            card_keys::CREATOR => Ok(Self::parse_creator(buf)?),
-            card_keys::XORGSUBF => Ok(Self::parse_subframe_x_position_in_binned_pixels(buf)?),
-            card_keys::YORGSUBF => Ok(Self::parse_subframe_y_position_in_binned_pixels(buf)?),
-            card_keys::XBINNING => Ok(Self::parse_binned_pixels_x(buf)?),
-            card_keys::YBINNING => Ok(Self::parse_binned_pixels_y(buf)?),
-            card_keys::CCDXBIN => Ok(Self::parse_ccd_binned_pixels_x(buf)?),
-            card_keys::CCDYBIN => Ok(Self::parse_ccd_binned_pixels_y(buf)?),
-            card_keys::XPIXSZ => Ok(Self::parse_pixel_size_x_with_binning_in_microns(buf)?),
-            card_keys::YPIXSZ => Ok(Self::parse_pixel_size_y_with_binning_in_microns(buf)?),
-            card_keys::IMAGETYP => Ok(Self::parse_image_type(buf)?),
-            card_keys::EXPOSURE => Ok(Self::parse_exposure(buf)?),
-            card_keys::RA => Ok(Self::parse_ra(buf)?),
-            card_keys::DEC => Ok(Self::parse_dec(buf)?),
-            card_keys::GUIDECAM => Ok(Self::parse_guide_cam(buf)?),
-            card_keys::FOCUSPOS => Ok(Self::parse_focus_position(buf)?),
-            card_keys::SITELONG => Ok(Self::parse_site_longitude(buf)?),
-            card_keys::SITELAT => Ok(Self::parse_site_latitude(buf)?),
-            card_keys::IMAGEW => Ok(Self::parse_image_width(buf)?),
-            card_keys::IMAGEH => Ok(Self::parse_image_height(buf)?),
             card_keys::COMMENT => Ok(Card::Comment(parse_comment_text(&buf[8..])?)),
             card_keys::HISTORY => Ok(Card::History(parse_comment_text(&buf[8..])?)),
             "CONTINUE" => parse_continuation(buf),
             "HIERARCH" => parse_hierarch(buf),
             card_keys::END => Ok(Card::End),
+            _ => {
+                if b"= " == &buf[8..10] {
+                    let value = parse_value(&buf[10..])?;
+                    if is_valid_key(key_word) {
+                        Self::new(key_word, value)
+                    } else {
+                        // Keep non standard keywords written by sloppy software readable
+                        Ok(Card::Value {
+                            name: key_word.to_string(),
+                            value,
+                        })
+                    }
+                } else {
+                    Ok(Card::Undefined(String::from_utf8_lossy(buf).into_owned()))
+                }
+            }
+        }
+    }
+
+    /// Creates a card from a keyword and its value. Known keywords are turned into their typed
+    /// card, and the value must be of the type the keyword requires. Keywords starting with
+    /// `HIERARCH ` create a HIERARCH card.
+    pub fn new(key: &str, value: Value) -> Result<Self> {
+        Self::parse_keyword(key, value).map_err(|err| with_keyword(err, key))
+    }
+
+    fn parse_keyword(key: &str, value: Value) -> Result<Self> {
+        if let Some(name) = key.strip_prefix("HIERARCH ")
+            && !name.trim_ascii().is_empty()
+        {
+            return Ok(Card::Hierarch {
+                name: hierarch_name(name),
+                value,
+            });
+        }
+
+        match key {
+            ""
+            | card_keys::COMMENT
+            | card_keys::HISTORY
+            | "CONTINUE"
+            | "HIERARCH"
+            | card_keys::END => Err(Error::InvalidKeyword {
+                keyword: key.into(),
+                message: "not a value keyword".into(),
+            }),
+            card_keys::AUTHOR => Self::parse_author(value),
+            card_keys::BITPIX => Self::parse_bitpix(value),
+            card_keys::BLANK => Self::parse_blank(value),
+            card_keys::BLOCKED => Self::parse_blocked(value),
+            card_keys::BSCALE => Self::parse_bscale(value),
+            card_keys::BUNIT => Self::parse_bunit(value),
+            card_keys::BZERO => Self::parse_bzero(value),
+            card_keys::DATAMAX => Self::parse_data_max(value),
+            card_keys::DATAMIN => Self::parse_data_min(value),
+            card_keys::DATE => Self::parse_date(value),
+            card_keys::DATE_OBS => Self::parse_date_observed(value),
+            card_keys::EPOCH => Self::parse_epoch(value),
+            card_keys::EQUINOX => Self::parse_equinox(value),
+            card_keys::EXTEND => Self::parse_extend(value),
+            card_keys::EXTLEVEL => Self::parse_extension_level(value),
+            card_keys::EXTNAME => Self::parse_extension_name(value),
+            card_keys::EXTVER => Self::parse_extension_version(value),
+            card_keys::GCOUNT => Self::parse_group_count(value),
+            card_keys::GROUPS => Self::parse_groups(value),
+            card_keys::INSTRUME => Self::parse_instrument(value),
+            card_keys::NAXIS => Self::parse_naxis(value),
+            card_keys::OBJECT => Self::parse_object(value),
+            card_keys::OBSERVER => Self::parse_observer(value),
+            card_keys::ORIGIN => Self::parse_origin(value),
+            card_keys::PCOUNT => Self::parse_pcount(value),
+            card_keys::REFERENC => Self::parse_reference(value),
+            card_keys::SIMPLE => Self::parse_simple(value),
+            card_keys::TELESCOP => Self::parse_telescope(value),
+            card_keys::TFIELDS => Self::parse_table_fields(value),
+            card_keys::THEAP => Self::parse_table_heap(value),
+            card_keys::XTENSION => Self::parse_xtension(value),
+            card_keys::FOCALLEN => Self::parse_focal_length(value),
+            card_keys::EXPTIME => Self::parse_exposure_time(value),
+            card_keys::CCD_TEMP => Self::parse_ccd_temperature(value),
+            card_keys::BAYERPAT => Self::parse_bayer_pattern(value),
+            card_keys::CREATOR => Self::parse_creator(value),
+            card_keys::XORGSUBF => Self::parse_subframe_x_position_in_binned_pixels(value),
+            card_keys::YORGSUBF => Self::parse_subframe_y_position_in_binned_pixels(value),
+            card_keys::XBINNING => Self::parse_binned_pixels_x(value),
+            card_keys::YBINNING => Self::parse_binned_pixels_y(value),
+            card_keys::CCDXBIN => Self::parse_ccd_binned_pixels_x(value),
+            card_keys::CCDYBIN => Self::parse_ccd_binned_pixels_y(value),
+            card_keys::XPIXSZ => Self::parse_pixel_size_x_with_binning_in_microns(value),
+            card_keys::YPIXSZ => Self::parse_pixel_size_y_with_binning_in_microns(value),
+            card_keys::IMAGETYP => Self::parse_image_type(value),
+            card_keys::EXPOSURE => Self::parse_exposure(value),
+            card_keys::RA => Self::parse_ra(value),
+            card_keys::DEC => Self::parse_dec(value),
+            card_keys::GUIDECAM => Self::parse_guide_cam(value),
+            card_keys::FOCUSPOS => Self::parse_focus_position(value),
+            card_keys::SITELONG => Self::parse_site_longitude(value),
+            card_keys::SITELAT => Self::parse_site_latitude(value),
+            card_keys::IMAGEW => Self::parse_image_width(value),
+            card_keys::IMAGEH => Self::parse_image_height(value),
             key if key.starts_with(card_keys::PREFIX_CDELT_N) => {
-                Ok(Self::parse_coordinate_delta(key, buf)?)
+                Self::parse_coordinate_delta(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_CROTA_N) => {
-                Ok(Self::parse_coordinate_rotation(key, buf)?)
+                Self::parse_coordinate_rotation(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_CRPIX_N) => {
-                Ok(Self::parse_coordinate_reference_pixel(key, buf)?)
+                Self::parse_coordinate_reference_pixel(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_CRVAL_N) => {
-                Ok(Self::parse_coordinate_value_at_pixel(key, buf)?)
+                Self::parse_coordinate_value_at_pixel(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_CTYPE_N) => {
-                Ok(Self::parse_coordinate_axis_name(key, buf)?)
+                Self::parse_coordinate_axis_name(key, value)
             }
-            key if key.starts_with(card_keys::PREFIX_NAXIS_N) => Ok(Self::parse_naxis_n(key, buf)?),
+            key if key.starts_with(card_keys::PREFIX_NAXIS_N) => Self::parse_naxis_n(key, value),
             key if key.starts_with(card_keys::PREFIX_PSCAL_N) => {
-                Ok(Self::parse_parameter_scaling_factor(key, buf)?)
+                Self::parse_parameter_scaling_factor(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_PTYPE_N) => {
-                Ok(Self::parse_parameter_type(key, buf)?)
+                Self::parse_parameter_type(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_PZERO_N) => {
-                Ok(Self::parse_parameter_scaling_zero_point(key, buf)?)
+                Self::parse_parameter_scaling_zero_point(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_TBCOL_N) => {
-                Ok(Self::parse_table_column(key, buf)?)
+                Self::parse_table_column(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_TDIM_N) => {
-                Ok(Self::parse_table_dimensions(key, buf)?)
+                Self::parse_table_dimensions(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_TFORM_N) => {
-                Ok(Self::parse_table_column_format(key, buf)?)
+                Self::parse_table_column_format(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_TDISP_N) => {
-                Ok(Self::parse_table_display_format(key, buf)?)
+                Self::parse_table_display_format(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_TNULL_N) => {
-                Ok(Self::parse_table_null_value(key, buf)?)
+                Self::parse_table_null_value(key, value)
             }
             key if key.starts_with(card_keys::PREFIX_TSCAL_N) => {
-                Ok(Self::parse_table_scaling_factor(key, buf)?)
-            }
-            key if key.starts_with(card_keys::PREFIX_TTYPE_N) => {
-                Ok(Self::parse_table_type(key, buf)?)
-            }
-            key if key.starts_with(card_keys::PREFIX_TUNIT_N) => {
-                Ok(Self::parse_table_unit(key, buf)?)
+                Self::parse_table_scaling_factor(key, value)
             }
+            key if key.starts_with(card_keys::PREFIX_TTYPE_N) => Self::parse_table_type(key, value),
+            key if key.starts_with(card_keys::PREFIX_TUNIT_N) => Self::parse_table_unit(key, value),
             key if key.starts_with(card_keys::PREFIX_TZERO_N) => {
-                Ok(Self::parse_table_scaling_zero_point(key, buf)?)
-            }
-            _ => {
-                if b"= " == &buf[8..10] {
-                    Ok(Card::Value {
-                        name: key_word.to_string(),
-                        value: parse_value(&buf[10..])?,
-                    })
-                } else {
-                    Ok(Card::Undefined(String::from_utf8_lossy(buf).into_owned()))
-                }
+                Self::parse_table_scaling_zero_point(key, value)
             }
+            key if is_valid_key(key) => Ok(Card::Value {
+                name: key.to_string(),
+                value,
+            }),
+            key => Err(Error::InvalidKeyword {
+                keyword: key.into(),
+                message: "keywords are up to 8 characters of A-Z, 0-9, - and _".into(),
+            }),
         }
     }
 
-    fn parse_author(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_author(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::Author { value, comment })
         } else {
-            Err("Invalid author data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_bitpix(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_bitpix(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::Bitpix {
                 value: value.try_into()?,
                 comment,
             })
         } else {
-            Err("Invalid bitpix data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_blank(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_blank(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::Blank { value, comment })
         } else {
-            Err("Invalid blank data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_blocked(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_blocked(value: Value) -> Result<Self> {
         if let Value::Logical { value, comment } = value {
             Ok(Card::Blocked { value, comment })
         } else {
-            Err("Invalid blocked data format".into())
+            Err(invalid_value("expected a logical"))
         }
     }
 
-    fn parse_bscale(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_bscale(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::BScale { value, comment })
         } else if let Value::Integer { value, comment } = value {
@@ -515,21 +560,19 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid bscale data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_bunit(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_bunit(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::BUnit { value, comment })
         } else {
-            Err("Invalid bunit data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_bzero(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_bzero(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::BZero { value, comment })
         } else if let Value::Integer { value, comment } = value {
@@ -539,235 +582,208 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid bzero data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_data_max(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_data_max(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::DataMax { value, comment })
         } else {
-            Err("Invalid data max data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_data_min(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_data_min(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::DataMin { value, comment })
         } else {
-            Err("Invalid data min data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_date(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_date(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
-            let value: NaiveDateTime = value.parse()?;
-            let value = value.and_utc();
+            let value = parse_date_time(&value)?;
             Ok(Card::Date { value, comment })
         } else {
-            Err("Invalid date data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_date_observed(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_date_observed(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
-            let value: NaiveDateTime = value.parse()?;
-            let value = value.and_utc();
+            let value = parse_date_time(&value)?;
             Ok(Card::DateObserved { value, comment })
         } else {
-            Err("Invalid date observed data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_epoch(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_epoch(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::Epoch { value, comment })
         } else {
-            Err("Invalid epoch data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_equinox(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_equinox(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::Equinox { value, comment })
         } else {
-            Err("Invalid equinox data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_extend(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_extend(value: Value) -> Result<Self> {
         if let Value::Logical { value, comment } = value {
             Ok(Card::Extend { value, comment })
         } else {
-            Err("Invalid extend data format".into())
+            Err(invalid_value("expected a logical"))
         }
     }
 
-    fn parse_extension_level(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_extension_level(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::ExtensionLevel { value, comment })
         } else {
-            Err("Invalid extension level data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_extension_name(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_extension_name(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::ExtensionName { value, comment })
         } else {
-            Err("Invalid extension name data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_extension_version(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_extension_version(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::ExtensionVersion { value, comment })
         } else {
-            Err("Invalid extension version data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_group_count(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_group_count(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::GroupCount { value, comment })
         } else {
-            Err("Invalid group count data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_groups(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_groups(value: Value) -> Result<Self> {
         if let Value::Logical { value, comment } = value {
             Ok(Card::Groups { value, comment })
         } else {
-            Err("Invalid groups data format".into())
+            Err(invalid_value("expected a logical"))
         }
     }
 
-    fn parse_instrument(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_instrument(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::Instrument { value, comment })
         } else {
-            Err("Invalid instrument data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_naxis(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_naxis(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::NAxis { value, comment })
         } else {
-            Err("Invalid naxis data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_object(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_object(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::Object { value, comment })
         } else {
-            Err("Invalid object data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_observer(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_observer(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::Observer { value, comment })
         } else {
-            Err("Invalid observer data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_origin(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_origin(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::Origin { value, comment })
         } else {
-            Err("Invalid origin data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_pcount(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_pcount(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::ParameterCount { value, comment })
         } else {
-            Err("Invalid pcount data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_reference(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_reference(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::Reference { value, comment })
         } else {
-            Err("Invalid referenc data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_simple(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_simple(value: Value) -> Result<Self> {
         if let Value::Logical { value, comment } = value {
             Ok(Card::Simple { value, comment })
         } else {
-            Err("Invalid simple data format".into())
+            Err(invalid_value("expected a logical"))
         }
     }
 
-    fn parse_telescope(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_telescope(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::Telescope { value, comment })
         } else {
-            Err("Invalid telescope data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_table_fields(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_table_fields(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::TableFields { value, comment })
         } else {
-            Err("Invalid tfields data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_table_heap(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_table_heap(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::TableHeap { value, comment })
         } else {
-            Err("Invalid theap data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_xtension(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_xtension(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::Xtension {
                 value: value.try_into()?,
                 comment,
             })
         } else {
-            Err("Invalid xtension data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_focal_length(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_focal_length(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::FocalLength { value, comment })
         } else if let Value::Integer { value, comment } = value {
@@ -776,234 +792,196 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid focallen data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_exposure_time(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_exposure_time(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
-            let value = std::time::Duration::from_secs_f64(value);
+            let value = core::time::Duration::from_secs_f64(value);
             Ok(Card::ExposureTime { value, comment })
         } else {
-            Err("Invalid exptime data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_ccd_temperature(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_ccd_temperature(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::CCDTemperature { value, comment })
         } else {
-            Err("Invalid ccd-temp data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_bayer_pattern(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_bayer_pattern(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::BayerPattern {
                 value: value.try_into()?,
                 comment,
             })
         } else {
-            Err("Invalid bayer pattern data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_creator(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_creator(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::Creator { value, comment })
         } else {
-            Err("Invalid creator data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_subframe_x_position_in_binned_pixels(
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_subframe_x_position_in_binned_pixels(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::SubframeXPositionInBinnedPixels { value, comment })
         } else {
-            Err("Invalid XORGSUBF data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_subframe_y_position_in_binned_pixels(
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_subframe_y_position_in_binned_pixels(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::SubframeXPositionInBinnedPixels { value, comment })
         } else {
-            Err("Invalid YORGSUBF data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_binned_pixels_x(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_binned_pixels_x(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::BinnedPixelsX { value, comment })
         } else {
-            Err("Invalid XBINNING data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_binned_pixels_y(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_binned_pixels_y(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::BinnedPixelsY { value, comment })
         } else {
-            Err("Invalid YBINNING data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_ccd_binned_pixels_x(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_ccd_binned_pixels_x(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::CCDBinnedPixelsX { value, comment })
         } else {
-            Err("Invalid CCDXBIN data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_ccd_binned_pixels_y(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_ccd_binned_pixels_y(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::CCDBinnedPixelsY { value, comment })
         } else {
-            Err("Invalid CCDYBIN data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_pixel_size_x_with_binning_in_microns(
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_pixel_size_x_with_binning_in_microns(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::PixelSizeXWithBinningInMicrons { value, comment })
         } else {
-            Err("Invalid XPIXSZ data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_pixel_size_y_with_binning_in_microns(
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_pixel_size_y_with_binning_in_microns(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::PixelSizeYWithBinningInMicrons { value, comment })
         } else {
-            Err("Invalid YPIXSZ data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_image_type(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_image_type(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::ImageType {
                 value: value.into(),
                 comment,
             })
         } else {
-            Err("Invalid IMAGETYP data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_exposure(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_exposure(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
-            let value = std::time::Duration::from_secs_f64(value);
+            let value = core::time::Duration::from_secs_f64(value);
             Ok(Card::Exposure { value, comment })
         } else {
-            Err("Invalid EXPOSURE data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_ra(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_ra(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::Ra { value, comment })
         } else {
-            Err("Invalid RA data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_dec(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_dec(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::Dec { value, comment })
         } else {
-            Err("Invalid DEC data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_guide_cam(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_guide_cam(value: Value) -> Result<Self> {
         if let Value::String { value, comment } = value {
             Ok(Card::GuideCam { value, comment })
         } else {
-            Err("Invalid GUIDECAM data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_focus_position(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_focus_position(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::FocusPosition { value, comment })
         } else {
-            Err("Invalid FOCUSPOS data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_site_longitude(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_site_longitude(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::SiteLongitude { value, comment })
         } else {
-            Err("Invalid SITELONG data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_site_latitude(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_site_latitude(value: Value) -> Result<Self> {
         if let Value::Float { value, comment } = value {
             Ok(Card::SiteLatitude { value, comment })
         } else {
-            Err("Invalid SITELAT data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_image_width(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_image_width(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::ImageWidth { value, comment })
         } else {
-            Err("Invalid IMAGEW data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_image_height(buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
+    fn parse_image_height(value: Value) -> Result<Self> {
         if let Value::Integer { value, comment } = value {
             Ok(Card::ImageHeight { value, comment })
         } else {
-            Err("Invalid IMAGEH data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_coordinate_delta(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_CDELT_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_coordinate_delta(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_CDELT_N)?;
 
         if let Value::Float { value, comment } = value {
             Ok(Card::CoordinateDeltaN {
@@ -1012,20 +990,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid cdeltn data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_coordinate_rotation(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_CROTA_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_coordinate_rotation(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_CROTA_N)?;
 
         if let Value::Float { value, comment } = value {
             Ok(Card::CoordinateRotationN {
@@ -1034,20 +1004,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid crotan data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_coordinate_reference_pixel(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_CRPIX_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_coordinate_reference_pixel(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_CRPIX_N)?;
 
         if let Value::Float { value, comment } = value {
             Ok(Card::CoordinateReferencePixelN {
@@ -1056,20 +1018,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid crpixn data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_coordinate_value_at_pixel(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_CRVAL_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_coordinate_value_at_pixel(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_CRVAL_N)?;
 
         if let Value::Float { value, comment } = value {
             Ok(Card::CoordinateValueAtPixelN {
@@ -1078,20 +1032,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid crvaln data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_coordinate_axis_name(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_CTYPE_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_coordinate_axis_name(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_CTYPE_N)?;
 
         if let Value::String { value, comment } = value {
             Ok(Card::CoordinateAxisNameN {
@@ -1100,17 +1046,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid crvaln data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_naxis_n(key: &str, buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_NAXIS_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_naxis_n(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_NAXIS_N)?;
 
         if let Value::Integer { value, comment } = value {
             Ok(Card::NAxisN {
@@ -1119,20 +1060,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid naxisn data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_parameter_scaling_factor(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_PSCAL_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_parameter_scaling_factor(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_PSCAL_N)?;
 
         if let Value::Float { value, comment } = value {
             Ok(Card::ParameterScalingFactorN {
@@ -1141,20 +1074,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid PSCALN data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_parameter_type(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_PTYPE_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_parameter_type(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_PTYPE_N)?;
 
         if let Value::String { value, comment } = value {
             Ok(Card::ParameterTypeN {
@@ -1163,20 +1088,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid PTYPEN data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_parameter_scaling_zero_point(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_PZERO_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_parameter_scaling_zero_point(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_PZERO_N)?;
 
         if let Value::Float { value, comment } = value {
             Ok(Card::ParameterScalingZeroPointN {
@@ -1185,17 +1102,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid PZERON data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_table_column(key: &str, buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_TBCOL_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_table_column(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_TBCOL_N)?;
 
         if let Value::Integer { value, comment } = value {
             Ok(Card::TableColumnN {
@@ -1204,17 +1116,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid TBCOLN data format".into())
+            Err(invalid_value("expected an integer"))
         }
     }
 
-    fn parse_table_dimensions(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key.replace(card_keys::PREFIX_TDIM_N, "").parse::<usize>()? - 1;
+    fn parse_table_dimensions(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_TDIM_N)?;
 
         if let Value::String { value, comment } = value {
             Ok(Card::TableDimensionsN {
@@ -1223,20 +1130,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid TDIMN data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_table_column_format(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_TFORM_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_table_column_format(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_TFORM_N)?;
 
         if let Value::String { value, comment } = value {
             Ok(Card::TableFormatN {
@@ -1245,20 +1144,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid TFORMN data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_table_display_format(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_TDISP_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_table_display_format(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_TDISP_N)?;
 
         if let Value::String { value, comment } = value {
             Ok(Card::TableDisplayFormatN {
@@ -1267,20 +1158,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid TDISPN data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_table_null_value(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_TNULL_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_table_null_value(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_TNULL_N)?;
 
         if let Value::String { value, comment } = value {
             Ok(Card::TableNullValueN {
@@ -1289,20 +1172,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid TNULLN data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_table_scaling_factor(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_TSCAL_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_table_scaling_factor(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_TSCAL_N)?;
 
         if let Value::Float { value, comment } = value {
             Ok(Card::TableScalingFactorN {
@@ -1311,17 +1186,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid TSCALN data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
-    fn parse_table_type(key: &str, buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_TTYPE_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_table_type(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_TTYPE_N)?;
 
         if let Value::String { value, comment } = value {
             Ok(Card::TableTypeN {
@@ -1330,17 +1200,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid TTYPEN data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_table_unit(key: &str, buf: &[u8; 80]) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_TUNIT_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_table_unit(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_TUNIT_N)?;
 
         if let Value::String { value, comment } = value {
             Ok(Card::TableUnitN {
@@ -1349,20 +1214,12 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid TUNITN data format".into())
+            Err(invalid_value("expected a string"))
         }
     }
 
-    fn parse_table_scaling_zero_point(
-        key: &str,
-        buf: &[u8; 80],
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let value = parse_value(&buf[10..])?;
-
-        let index = key
-            .replace(card_keys::PREFIX_TZERO_N, "")
-            .parse::<usize>()?
-            - 1;
+    fn parse_table_scaling_zero_point(key: &str, value: Value) -> Result<Self> {
+        let index = key_index(key, card_keys::PREFIX_TZERO_N)?;
 
         if let Value::Float { value, comment } = value {
             Ok(Card::TableScalingZeroPointN {
@@ -1371,7 +1228,7 @@ impl Card {
                 comment,
             })
         } else {
-            Err("Invalid TZERON data format".into())
+            Err(invalid_value("expected a float"))
         }
     }
 
@@ -1417,9 +1274,9 @@ impl Card {
             Card::BayerPattern { .. } => card_keys::BAYERPAT.to_string(),
             Card::Value { name, .. } => name.to_string(),
             Card::Continuation { .. } => "CONTINUE".to_string(),
-            Card::Hierarch { .. } => "HIERARCH".to_string(),
+            Card::Hierarch { name, .. } => format!("HIERARCH {}", name),
             Card::Space => "".to_string(),
-            Card::Undefined(_) => "".to_string(),
+            Card::Undefined(text) => text.chars().take(8).collect::<String>().trim().to_string(),
             Card::CoordinateDeltaN { index, .. } => {
                 format!("{}{}", card_keys::PREFIX_CDELT_N, index + 1)
             }
@@ -1489,22 +1346,325 @@ impl Card {
             Card::ImageHeight { .. } => card_keys::IMAGEH.to_string(),
         }
     }
+
+    /// The value of this card together with its comment
+    pub fn value(&self) -> Value {
+        self.into()
+    }
+
+    /// The comment of this card, or the text of COMMENT and HISTORY cards
+    pub fn comment(&self) -> Option<String> {
+        match self {
+            Card::Comment(text) | Card::History(text) => Some(text.trim_end().into()),
+            card => {
+                Some(Value::from(card).comment_to_string()).filter(|comment| !comment.is_empty())
+            }
+        }
+    }
+
+    /// Formats this card as a single 80 character header record, following the fixed format
+    /// rules of FITSv4, section 4.2. Values that do not fit are truncated, and comments are
+    /// shortened or left out when there is no room for them.
+    pub fn to_record(&self) -> [u8; 80] {
+        let keyword = format!("{: <8}", self.key());
+        let record = match self {
+            Card::Comment(text) | Card::History(text) => format!("{}{}", keyword, text),
+            Card::Space => String::new(),
+            Card::End => card_keys::END.to_string(),
+            Card::Undefined(text) => text.clone(),
+            Card::Continuation { string, comment } => {
+                let prefix = format!("{}  ", keyword);
+                let value = string
+                    .as_deref()
+                    .map(|string| quote_string(string, 80 - prefix.len()))
+                    .unwrap_or_default();
+                format_record(&prefix, &value, comment.as_deref())
+            }
+            Card::Hierarch { value, .. } => {
+                // The ESO convention writes the value directly after the equals sign instead of
+                // right justifying it, so that long names leave room for the value
+                format_value_record(&format!("{} = ", self.key()), value, 0)
+            }
+            Card::Date { value, comment } | Card::DateObserved { value, comment } => {
+                let value = Value::String {
+                    value: value.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+                    comment: comment.clone(),
+                };
+                format_value_record(&format!("{}= ", keyword), &value, 20)
+            }
+            _ => format_value_record(&format!("{}= ", keyword), &Value::from(self), 20),
+        };
+
+        to_record_bytes(&record)
+    }
+
+    /// Formats this card as one or more header records. String values that do not fit in a single
+    /// record are split over CONTINUE records, using the OGIP long string convention.
+    pub fn to_records(&self) -> Vec<[u8; 80]> {
+        let prefix = match self {
+            Card::Comment(_)
+            | Card::History(_)
+            | Card::Space
+            | Card::End
+            | Card::Undefined(_)
+            | Card::Continuation { .. }
+            | Card::Date { .. }
+            | Card::DateObserved { .. } => return vec![self.to_record()],
+            Card::Hierarch { .. } => format!("{} = ", self.key()),
+            _ => format!("{: <8}= ", self.key()),
+        };
+        let Value::String { value, comment } = Value::from(self) else {
+            return vec![self.to_record()];
+        };
+        let value = value.trim_end();
+        if escaped_len(value) + 2 <= 80usize.saturating_sub(prefix.len()) {
+            return vec![self.to_record()];
+        }
+
+        let mut records = vec![];
+        let mut prefix = prefix;
+        let mut rest = value;
+        loop {
+            // Leave room for the quotes and the ampersand marking the string as continued
+            let (chunk, tail) = split_escaped(rest, 80usize.saturating_sub(prefix.len() + 3));
+            let chunk = chunk.replace('\'', "''");
+            if tail.is_empty() {
+                match comment.as_deref() {
+                    // A comment that doesn't fit after the last chunk gets records of its own
+                    Some(comment) if prefix.len() + chunk.len() + 5 + comment.len() > 80 => {
+                        records.push(to_record_bytes(&format!("{}'{}&'", prefix, chunk)));
+                        records.extend(comment_records(comment));
+                    }
+                    comment => {
+                        let record = format_record(&prefix, &format!("'{}'", chunk), comment);
+                        records.push(to_record_bytes(&record));
+                    }
+                }
+                break;
+            }
+            records.push(to_record_bytes(&format!("{}'{}&'", prefix, chunk)));
+            prefix = "CONTINUE  ".into();
+            rest = tail;
+        }
+        records
+    }
+}
+
+/// CONTINUE records carrying only a comment, which end a continued string. Long comments are
+/// split at spaces, as the parts are joined with a space when read.
+fn comment_records(comment: &str) -> Vec<[u8; 80]> {
+    let max_len = 80 - "CONTINUE  '&' / ".len();
+    let mut records = vec![];
+    let mut rest = comment;
+    while rest.len() > max_len {
+        let mut split = max_len;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        let (part, tail) = if rest[split..].starts_with(' ') {
+            (&rest[..split], &rest[split + 1..])
+        } else if let Some(space) = rest[..split].rfind(' ').filter(|space| *space > 0) {
+            (&rest[..space], &rest[space + 1..])
+        } else {
+            rest.split_at(split)
+        };
+        records.push(to_record_bytes(&format!("CONTINUE  '&' / {}", part)));
+        rest = tail;
+    }
+    records.push(to_record_bytes(&format!("CONTINUE  '' / {}", rest)));
+    records
+}
+
+fn to_record_bytes(record: &str) -> [u8; 80] {
+    let mut buf = [b' '; 80];
+    for (target, byte) in buf.iter_mut().zip(record.bytes()) {
+        // Header records may only contain printable ASCII, cf. FITSv4, section 4.1.1
+        *target = if (0x20..=0x7e).contains(&byte) {
+            byte
+        } else {
+            b'?'
+        };
+    }
+    buf
 }
 
-fn parse_comment_text(buf: &[u8]) -> Result<String, Box<dyn Error + Send + Sync>> {
-    let raw = std::str::from_utf8(buf.trim_ascii())?;
+fn escaped_len(value: &str) -> usize {
+    value.len() + value.matches('\'').count()
+}
+
+/// Splits off the longest start of `value` that fits in `max_len` characters once its quotes are
+/// doubled. At least one character is split off so that callers always make progress.
+fn split_escaped(value: &str, max_len: usize) -> (&str, &str) {
+    let mut len = 0;
+    for (index, ch) in value.char_indices() {
+        let escaped_len = if ch == '\'' { 2 } else { ch.len_utf8() };
+        if len + escaped_len > max_len && index > 0 {
+            return value.split_at(index);
+        }
+        len += escaped_len;
+    }
+    (value, "")
+}
+
+/// Formats a value record, numbers and logicals are right justified to `width` characters after
+/// the prefix, which puts them in column 30 for standard keywords, cf. FITSv4, section 4.2
+fn format_value_record(prefix: &str, value: &Value, width: usize) -> String {
+    let available = 80usize.saturating_sub(prefix.len());
+    let formatted = match value {
+        Value::Integer { value, .. } => format!("{: >width$}", value),
+        Value::Float { value, .. } => format!("{: >width$}", format_float(*value)),
+        Value::Logical { value, .. } => {
+            format!("{: >width$}", if *value { "T" } else { "F" })
+        }
+        Value::String { value, .. } => quote_string(value, available),
+        // The real part ends in column 30 and the imaginary part in column 50, cf. FITSv4,
+        // section 4.2.5
+        Value::ComplexInteger {
+            real, imaginary, ..
+        } => format!(
+            "{: >width$},{: >imaginary_width$})",
+            format!("({}", real),
+            imaginary,
+            imaginary_width = width.saturating_sub(1)
+        ),
+        Value::ComplexFloat {
+            real, imaginary, ..
+        } => format!(
+            "{: >width$},{: >imaginary_width$})",
+            format!("({}", format_float(*real)),
+            format_float(*imaginary),
+            imaginary_width = width.saturating_sub(1)
+        ),
+        Value::Undefined => String::new(),
+        Value::Invalid(text) => text.trim_ascii().to_string(),
+    };
+
+    let comment = value.comment_to_string();
+    if comment.is_empty() {
+        format_record(prefix, &formatted, None)
+    } else {
+        format_record(prefix, &formatted, Some(&comment))
+    }
+}
+
+/// Joins the parts of a record, the comment is only added if there is room for at least the
+/// separating slash
+fn format_record(prefix: &str, value: &str, comment: Option<&str>) -> String {
+    let mut record = format!("{}{}", prefix, value);
+    if let Some(comment) = comment
+        && record.len() + 3 < 80
+    {
+        record.push_str(" / ");
+        record.push_str(comment);
+    }
+    record
+}
+
+/// Encloses a string in single quotes, doubling any embedded quotes and padding it to at least 8
+/// characters. The string is truncated so that the quoted value is at most `max_len` characters,
+/// without splitting an escaped quote.
+fn quote_string(value: &str, max_len: usize) -> String {
+    let mut quoted = String::from("'");
+    for ch in value.trim_end().chars() {
+        let escaped_len = if ch == '\'' { 2 } else { ch.len_utf8() };
+        if quoted.len() + escaped_len + 1 > max_len {
+            break;
+        }
+        quoted.push(ch);
+        if ch == '\'' {
+            quoted.push('\'');
+        }
+    }
+    while quoted.len() < 9 {
+        quoted.push(' ');
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Formats a float so it is always read back as a float, with a decimal point and an upper case
+/// exponent, cf. FITSv4, section 4.2.4
+fn format_float(value: f64) -> String {
+    let formatted = format!("{:?}", value);
+    if !formatted.contains('e') {
+        return formatted;
+    }
+
+    let formatted = format!("{:E}", value);
+    if formatted.contains('.') {
+        formatted
+    } else {
+        formatted.replacen('E', ".0E", 1)
+    }
+}
+
+/// Keywords are up to 8 characters of upper case letters, digits, hyphen and underscore, cf.
+/// FITSv4, section 4.1.2.1
+fn is_valid_key(key: &str) -> bool {
+    key.len() <= 8
+        && key
+            .bytes()
+            .all(|byte| matches!(byte, b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_'))
+}
+
+fn parse_comment_text(buf: &[u8]) -> Result<String> {
+    let raw =
+        core::str::from_utf8(buf.trim_ascii()).map_err(|err| invalid_card(err.to_string()))?;
     Ok(raw.trim_ascii().into())
 }
 
-fn parse_continuation(_buf: &[u8; 80]) -> Result<Card, Box<dyn Error + Send + Sync>> {
-    Ok(Card::Undefined("".into()))
+/// Parses a FITS date, which has no time zone and is taken as UTC. Dates with an offset, as
+/// returned by [`Value::from`] for date cards, are accepted as well.
+fn parse_date_time(value: &str) -> Result<DateTime<Utc>> {
+    match value.parse::<NaiveDateTime>() {
+        Ok(value) => Ok(value.and_utc()),
+        Err(err) => DateTime::parse_from_rfc3339(value)
+            .map(|value| value.to_utc())
+            .map_err(|_| invalid_value(err.to_string())),
+    }
 }
 
-fn parse_hierarch(_buf: &[u8; 80]) -> Result<Card, Box<dyn Error + Send + Sync>> {
-    Ok(Card::Undefined("".into()))
+/// Parses a CONTINUE card of the long string convention, cf. FITSv4, section 4.2.1.2
+fn parse_continuation(buf: &[u8; 80]) -> Result<Card> {
+    let (value, comment) = split_value_and_comment(&buf[8..])?;
+    let string = if value.starts_with('\'') {
+        Some(parse_string(value)?)
+    } else {
+        None
+    };
+    Ok(Card::Continuation { string, comment })
 }
 
-fn parse_value(buf: &[u8]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+/// Parses an ESO HIERARCH card, `HIERARCH ESO DET CHIP TEMP = -120.0 / comment`. The name is
+/// everything between the HIERARCH keyword and the equals sign.
+fn parse_hierarch(buf: &[u8; 80]) -> Result<Card> {
+    let Some(separator) = buf[8..].iter().position(|byte| *byte == b'=') else {
+        return Ok(Card::Undefined(String::from_utf8_lossy(buf).into_owned()));
+    };
+    let name = core::str::from_utf8(&buf[8..8 + separator])
+        .map_err(|err| invalid_card(err.to_string()))?;
+
+    Ok(Card::Hierarch {
+        name: hierarch_name(name),
+        value: parse_value(&buf[9 + separator..])?,
+    })
+}
+
+/// Normalizes a HIERARCH name so that `ESO.DET.CHIP.TEMP`, `ESO DET  CHIP TEMP` and
+/// `HIERARCH ESO DET CHIP TEMP` all refer to the same keyword
+pub(crate) fn hierarch_name(name: &str) -> String {
+    let name = name.trim_ascii();
+    let name = name
+        .strip_prefix("HIERARCH ")
+        .or_else(|| name.strip_prefix("HIERARCH."))
+        .unwrap_or(name);
+    name.split(|ch: char| ch == '.' || ch.is_ascii_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_value(buf: &[u8]) -> Result<Value> {
     let (v, c) = split_value_and_comment(buf)?;
     if let Some(ch) = v.chars().next() {
         match ch {
@@ -1520,7 +1680,7 @@ fn parse_value(buf: &[u8]) -> Result<Value, Box<dyn Error + Send + Sync>> {
                 value: false,
                 comment: c,
             }),
-            '(' => Err("Complex values not yet supported".into()),
+            '(' => parse_complex(v, c),
             '0'..='9' | '-' | '+' | '.' => parse_number(v, c),
             _ => Ok(Value::Invalid(String::from_utf8_lossy(buf).into_owned())),
         }
@@ -1529,7 +1689,7 @@ fn parse_value(buf: &[u8]) -> Result<Value, Box<dyn Error + Send + Sync>> {
     }
 }
 
-fn parse_empty_keyword_card(buf: &[u8; 80]) -> Result<Card, Box<dyn Error + Send + Sync>> {
+fn parse_empty_keyword_card(buf: &[u8; 80]) -> Result<Card> {
     let c = parse_comment_text(&buf[8..])?;
     if c.is_empty() {
         Ok(Card::Space)
@@ -1538,60 +1698,92 @@ fn parse_empty_keyword_card(buf: &[u8; 80]) -> Result<Card, Box<dyn Error + Send
     }
 }
 
-pub fn split_value_and_comment(
-    buf: &[u8],
-) -> Result<(String, Option<String>), Box<dyn Error + Send + Sync>> {
-    let raw = std::str::from_utf8(buf.trim_ascii())?;
-
-    if raw.starts_with("'") {
-        let parts: Vec<_> = raw.split("'").collect();
-        if let Some(part) = parts.get(2) {
-            let comment_parts: Vec<_> = part.split("/").collect();
-            return Ok((
-                format!("'{}'", parts[1]).trim_ascii().into(),
-                comment_parts
-                    .get(1)
-                    .map(|i| i.trim_ascii())
-                    .map(|i| (*i).into()),
-            ));
+pub fn split_value_and_comment(buf: &[u8]) -> Result<(String, Option<String>)> {
+    let raw =
+        core::str::from_utf8(buf.trim_ascii()).map_err(|err| invalid_card(err.to_string()))?;
+
+    let value_end = if raw.starts_with('\'') {
+        // Skip over doubled quotes to find the closing quote, cf. FITSv4, section 4.2.1.1
+        let bytes = raw.as_bytes();
+        let mut index = 1;
+        loop {
+            match bytes.get(index) {
+                Some(b'\'') if bytes.get(index + 1) == Some(&b'\'') => index += 2,
+                Some(b'\'') => break index + 1,
+                Some(_) => index += 1,
+                None => break raw.find('/').unwrap_or(raw.len()),
+            }
         }
-    }
+    } else {
+        raw.find('/').unwrap_or(raw.len())
+    };
 
-    let parts: Vec<_> = raw.split("/").collect();
+    let (value, rest) = raw.split_at(value_end);
+    let comment = rest
+        .trim_ascii()
+        .strip_prefix('/')
+        .map(|comment| comment.trim_ascii().into());
 
-    Ok((
-        parts[0].trim_ascii().into(),
-        parts.get(1).map(|i| i.trim_ascii()).map(|i| (*i).into()),
-    ))
+    Ok((value.trim_ascii().into(), comment))
 }
 
-fn parse_string(s: String) -> Result<String, Box<dyn Error + Send + Sync>> {
+fn parse_string(s: String) -> Result<String> {
     let start_quote = s.starts_with("'");
-    let end_quote = s.ends_with("'");
+    let end_quote = s.len() > 1 && s.ends_with("'");
     let value: &str = match (start_quote, end_quote) {
         (true, true) => &s[1..s.len() - 1], // string enclosed in single quotes
         (false, false) => &s,               // comment string has no quotes
         (true, false) => {
-            return Err(format!("missing single quote at end, value was: {}", s).into());
+            return Err(invalid_card(format!(
+                "missing single quote at end, value was: {}",
+                s
+            )));
         }
         (false, true) => {
-            return Err(format!("missing single quote at start, value was: {}", s).into());
+            return Err(invalid_card(format!(
+                "missing single quote at start, value was: {}",
+                s
+            )));
         }
     };
 
-    if value.is_empty() {
-        Ok(value.to_string())
+    // Trailing spaces are not significant, but leading spaces are, cf. FITSv4, section 4.2.1.1
+    Ok(value.replace("''", "'").trim_end().to_string())
+}
+
+/// Parses a complex value, `(real, imaginary)`, where both parts are either integers or floats,
+/// cf. FITSv4, sections 4.2.5 and 4.2.6
+fn parse_complex(v: String, c: Option<String>) -> Result<Value> {
+    let parts = v
+        .strip_prefix('(')
+        .and_then(|v| v.strip_suffix(')'))
+        .and_then(|v| v.split_once(','))
+        .map(|(real, imaginary)| (real.trim_ascii(), imaginary.trim_ascii()));
+    let Some((real, imaginary)) = parts else {
+        return Ok(Value::Invalid(v));
+    };
+
+    if let (Ok(real), Ok(imaginary)) = (real.parse::<i64>(), imaginary.parse::<i64>()) {
+        return Ok(Value::ComplexInteger {
+            real,
+            imaginary,
+            comment: c,
+        });
+    }
+
+    let parse_float = |part: &str| part.replace('D', "E").parse::<f64>();
+    if let (Ok(real), Ok(imaginary)) = (parse_float(real), parse_float(imaginary)) {
+        Ok(Value::ComplexFloat {
+            real,
+            imaginary,
+            comment: c,
+        })
     } else {
-        let value = value.trim_end().to_string();
-        if value.is_empty() {
-            Ok("".to_string())
-        } else {
-            Ok(value)
-        }
+        Ok(Value::Invalid(v))
     }
 }
 
-fn parse_number(v: String, c: Option<String>) -> Result<Value, Box<dyn Error + Send + Sync>> {
+fn parse_number(v: String, c: Option<String>) -> Result<Value> {
     if v.is_empty() {
         Ok(Value::Undefined) // FITSv4, section 4.1.2.3
     } else if let Ok(val) = v.parse::<i64>() {
@@ -1619,3 +1811,50 @@ fn parse_number(v: String, c: Option<String>) -> Result<Value, Box<dyn Error + S
         }
     }
 }
+
+/// Error for a record that can not be parsed, the keyword is filled in by [`Card::try_from`]
+fn invalid_card(message: impl Into<String>) -> Error {
+    Error::InvalidCard {
+        keyword: String::new(),
+        message: message.into(),
+        position: None,
+    }
+}
+
+/// Error for a value that does not fit its keyword, the keyword is filled in by [`Card::new`]
+fn invalid_value(message: impl Into<String>) -> Error {
+    Error::InvalidValue {
+        keyword: String::new(),
+        message: message.into(),
+    }
+}
+
+/// Index of an indexed keyword like NAXISn, counting from 0
+fn key_index(key: &str, prefix: &str) -> Result<usize> {
+    match key.strip_prefix(prefix).map(|index| index.parse::<usize>()) {
+        Some(Ok(index)) if index > 0 => Ok(index - 1),
+        _ => Err(Error::InvalidKeyword {
+            keyword: key.into(),
+            message: format!("expected {} followed by an index starting at 1", prefix),
+        }),
+    }
+}
+
+fn with_keyword(err: Error, key: &str) -> Error {
+    match err {
+        Error::InvalidCard {
+            keyword,
+            message,
+            position,
+        } if keyword.is_empty() => Error::InvalidCard {
+            keyword: key.into(),
+            message,
+            position,
+        },
+        Error::InvalidValue { keyword, message } if keyword.is_empty() => Error::InvalidValue {
+            keyword: key.into(),
+            message,
+        },
+        err => err,
+    }
+}
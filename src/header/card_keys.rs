@@ -184,3 +184,6 @@ pub const SITELONG: &str = "SITELONG";
 pub const SITELAT: &str = "SITELAT";
 pub const IMAGEW: &str = "IMAGEW";
 pub const IMAGEH: &str = "IMAGEH";
+pub const LONGSTRN: &str = "LONGSTRN";
+pub const CHECKSUM: &str = "CHECKSUM";
+pub const DATASUM: &str = "DATASUM";
@@ -1,8 +1,5 @@
-use alloc::boxed::Box;
-use alloc::format;
 use alloc::string::String;
 use alloc::string::ToString;
-use core::error::Error;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ExtensionType {
@@ -22,14 +19,14 @@ impl From<ExtensionType> for String {
 }
 
 impl TryFrom<String> for ExtensionType {
-    type Error = Box<dyn Error + Send + Sync>;
+    type Error = crate::Error;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
         match value.to_uppercase().as_str() {
             "IMAGE" => Ok(ExtensionType::Image),
             "TABLE" => Ok(ExtensionType::AsciiTable),
             "BINTABLE" => Ok(ExtensionType::BinTable),
-            _ => Err(format!("Unknown extension type: {}", value).into()),
+            _ => Err(crate::Error::UnsupportedExtension(value)),
         }
     }
 }
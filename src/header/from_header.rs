@@ -0,0 +1,194 @@
+use crate::header::{Card, Header, Value};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::de::value::{SeqDeserializer, StrDeserializer, StringDeserializer};
+use serde::de::{DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+/// Deserializes the keywords of a header into a struct or map. Struct fields are matched against
+/// keywords ignoring case, use `#[serde(rename = "DATE-OBS")]` for keywords that are not valid
+/// field names. Missing keywords and keywords without a value become `None` for `Option` fields.
+pub fn from_header<T: DeserializeOwned>(header: &Header) -> crate::Result<T> {
+    T::deserialize(Deserializer { header })
+}
+
+struct Deserializer<'a> {
+    header: &'a Header,
+}
+
+impl<'de> serde::de::Deserializer<'de> for Deserializer<'_> {
+    type Error = crate::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let entries = self
+            .header
+            .iter()
+            .map(|card| (card.key(), card.value()))
+            .filter(|(_, value)| *value != Value::Undefined)
+            .collect();
+        visitor.visit_map(KeywordAccess::new(entries))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let entries = fields
+            .iter()
+            .filter_map(|field| {
+                let value = self
+                    .header
+                    .iter()
+                    .find(|card| card.key().eq_ignore_ascii_case(field))
+                    .map(Card::value)?;
+                (value != Value::Undefined).then(|| (field.to_string(), value))
+            })
+            .collect();
+        visitor.visit_map(KeywordAccess::new(entries))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct enum identifier ignored_any
+    }
+}
+
+/// Hands out the keywords found for a struct or map, with their values
+struct KeywordAccess {
+    entries: vec::IntoIter<(String, Value)>,
+    value: Option<(String, Value)>,
+}
+
+impl KeywordAccess {
+    fn new(entries: Vec<(String, Value)>) -> Self {
+        Self {
+            entries: entries.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for KeywordAccess {
+    type Error = crate::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        let deserializer: StrDeserializer<'_, crate::Error> = key.as_str().into_deserializer();
+        let result = seed.deserialize(deserializer).map(Some);
+        self.value = Some((key, value));
+        result
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (keyword, value) = self.value.take().expect("a key before its value");
+        seed.deserialize(ValueDeserializer { value })
+            .map_err(|err| match err {
+                crate::Error::DeserializationError(message) => {
+                    crate::Error::InvalidValue { keyword, message }
+                }
+                err => err,
+            })
+    }
+}
+
+/// Deserializes the value of a single card
+struct ValueDeserializer {
+    value: Value,
+}
+
+impl<'de> serde::de::Deserializer<'de> for ValueDeserializer {
+    type Error = crate::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Integer { value, .. } => visitor.visit_i64(value),
+            Value::Float { value, .. } => visitor.visit_f64(value),
+            Value::Logical { value, .. } => visitor.visit_bool(value),
+            Value::String { value, .. } => visitor.visit_string(value),
+            Value::ComplexInteger {
+                real, imaginary, ..
+            } => visitor.visit_seq(SeqDeserializer::new([real, imaginary].into_iter())),
+            Value::ComplexFloat {
+                real, imaginary, ..
+            } => visitor.visit_seq(SeqDeserializer::new([real, imaginary].into_iter())),
+            Value::Undefined => visitor.visit_unit(),
+            Value::Invalid(text) => Err(serde::de::Error::custom(alloc::format!(
+                "can not parse {}",
+                text.trim()
+            ))),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Undefined => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::String { value, .. } => {
+                let deserializer: StringDeserializer<crate::Error> = value.into_deserializer();
+                visitor.visit_enum(deserializer)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
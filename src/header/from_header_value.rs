@@ -0,0 +1,130 @@
+use crate::Result;
+use crate::error::Error;
+use crate::header::{BayerPattern, ImageType, Value};
+use alloc::format;
+use alloc::string::String;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use core::time::Duration;
+
+/// Conversion from a header value into a Rust type, used by [`crate::header::Header::get`]
+pub trait FromHeaderValue: Sized {
+    fn from_header_value(value: &Value) -> Result<Self>;
+}
+
+impl FromHeaderValue for Value {
+    fn from_header_value(value: &Value) -> Result<Self> {
+        Ok(value.clone())
+    }
+}
+
+impl FromHeaderValue for i64 {
+    fn from_header_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Integer { value, .. } => Ok(*value),
+            _ => Err(unexpected_type("an integer", value)),
+        }
+    }
+}
+
+impl FromHeaderValue for f64 {
+    fn from_header_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Float { value, .. } => Ok(*value),
+            // Many writers leave out the decimal point for whole numbers
+            Value::Integer { value, .. } => Ok(*value as f64),
+            _ => Err(unexpected_type("a float", value)),
+        }
+    }
+}
+
+impl FromHeaderValue for bool {
+    fn from_header_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Logical { value, .. } => Ok(*value),
+            _ => Err(unexpected_type("a logical", value)),
+        }
+    }
+}
+
+impl FromHeaderValue for String {
+    fn from_header_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::String { value, .. } => Ok(value.clone()),
+            _ => Err(unexpected_type("a string", value)),
+        }
+    }
+}
+
+impl FromHeaderValue for DateTime<Utc> {
+    fn from_header_value(value: &Value) -> Result<Self> {
+        let Value::String { value, .. } = value else {
+            return Err(unexpected_type("a date", value));
+        };
+
+        // FITS dates have no time zone and may leave out the time, cf. FITSv4, section 9.1.1
+        if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+            Ok(date.with_timezone(&Utc))
+        } else if let Ok(date) = value.parse::<NaiveDateTime>() {
+            Ok(date.and_utc())
+        } else if let Ok(date) = value.parse::<NaiveDate>() {
+            Ok(date.and_time(Default::default()).and_utc())
+        } else {
+            Err(invalid_value(format!("expected a date, found '{}'", value)))
+        }
+    }
+}
+
+impl FromHeaderValue for Duration {
+    fn from_header_value(value: &Value) -> Result<Self> {
+        let seconds = f64::from_header_value(value)
+            .map_err(|_| unexpected_type("a duration in seconds", value))?;
+        Duration::try_from_secs_f64(seconds).map_err(|_| {
+            invalid_value(format!("expected a duration in seconds, found {}", seconds))
+        })
+    }
+}
+
+impl FromHeaderValue for ImageType {
+    fn from_header_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::String { value, .. } => Ok(value.into()),
+            _ => Err(unexpected_type("an image type", value)),
+        }
+    }
+}
+
+impl FromHeaderValue for BayerPattern {
+    fn from_header_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::String { value, .. } => value.clone().try_into(),
+            _ => Err(unexpected_type("a bayer pattern", value)),
+        }
+    }
+}
+
+/// The keyword is filled in by [`crate::header::Header::get`]
+fn invalid_value(message: String) -> Error {
+    Error::InvalidValue {
+        keyword: String::new(),
+        message,
+    }
+}
+
+fn unexpected_type(expected: &str, value: &Value) -> Error {
+    let found = match value {
+        Value::Integer { .. } => "an integer",
+        Value::Float { .. } => "a float",
+        Value::Logical { .. } => "a logical",
+        Value::String { .. } => "a string",
+        Value::ComplexInteger { .. } => "a complex integer",
+        Value::ComplexFloat { .. } => "a complex float",
+        Value::Undefined => "an undefined value",
+        Value::Invalid(_) => "an invalid value",
+    };
+    invalid_value(format!(
+        "expected {}, found {} '{}'",
+        expected,
+        found,
+        value.value_to_string()
+    ))
+}
@@ -1,26 +1,136 @@
-use crate::header::card::Card;
+use crate::Result;
+use crate::card_position::CardPosition;
+use crate::error::Error;
+use crate::hdu::{ChecksumStatus, ChecksumVerification};
+use crate::header::card::{Card, hierarch_name};
 use crate::header::extension_type::ExtensionType;
+use crate::header::header_diff::diff;
 use crate::header::value::Value;
-use crate::header::{BayerPattern, Bitpix, ImageType, TableColumnFormat};
+use crate::header::{
+    BayerPattern, Bitpix, DiffOptions, FromHeaderValue, ImageType, KeywordDiff, TableColumnFormat,
+    card_keys,
+};
+use crate::open_options::OpenOptions;
+use crate::parse_warning::ParseWarning;
+#[cfg(feature = "std")]
 use crate::util::ReadSeek;
+use crate::util::{encode_checksum, ones_complement_sum};
 use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
 use alloc::vec::Vec;
 use chrono::{DateTime, Utc};
-use core::error::Error;
+use core::fmt;
 use core::fmt::Formatter;
-use std::io::{Cursor, Read};
-use std::{fmt, vec};
+#[cfg(feature = "std")]
+use std::io::{ErrorKind, Read};
 
 const CARD_NUM_BYTES: usize = 80;
+const BLOCK_NUM_BYTES: usize = 2880;
+// Columns 9 to 80 of COMMENT and HISTORY cards, cf. FITSv4, section 4.4.2.4
+const COMMENTARY_NUM_CHARS: usize = 72;
 
 #[derive(Clone, Default)]
 pub struct Header {
-    cards: Vec<Card>,
+    cards: Vec<HeaderCard>,
+
+    // Whether cards were added, changed or removed since the header was read
+    modified: bool,
+}
+
+/// A card together with the records it was read from, so that cards that were not changed are
+/// written back exactly as they were read
+#[derive(Clone)]
+struct HeaderCard {
+    card: Card,
+    // More than one record for strings continued over CONTINUE cards, none for added cards
+    records: Vec<[u8; CARD_NUM_BYTES]>,
+    modified: bool,
+}
+
+impl HeaderCard {
+    fn new(card: Card) -> Self {
+        Self {
+            card,
+            records: vec![],
+            modified: true,
+        }
+    }
+
+    fn read(card: Card, records: Vec<[u8; CARD_NUM_BYTES]>) -> Self {
+        Self {
+            card,
+            records,
+            modified: false,
+        }
+    }
+
+    fn to_records(&self) -> Vec<[u8; CARD_NUM_BYTES]> {
+        if self.modified {
+            self.card.to_records()
+        } else {
+            self.records.clone()
+        }
+    }
 }
 
 impl Header {
+    /// Creates the smallest valid primary header, describing an HDU without any data
+    pub(crate) fn new_primary() -> Self {
+        Self {
+            cards: vec![
+                Card::Simple {
+                    value: true,
+                    comment: Some("file does conform to FITS standard".into()),
+                },
+                Card::Bitpix {
+                    value: Bitpix::U8,
+                    comment: Some("number of bits per data pixel".into()),
+                },
+                Card::NAxis {
+                    value: 0,
+                    comment: Some("number of data axes".into()),
+                },
+                Card::End,
+            ]
+            .into_iter()
+            .map(HeaderCard::new)
+            .collect(),
+            modified: true,
+        }
+    }
+
+    /// Creates a header from cards in the order they should be written, adding END when missing
+    pub(crate) fn from_cards(mut cards: Vec<Card>) -> Self {
+        if cards.last() != Some(&Card::End) {
+            cards.push(Card::End);
+        }
+        Self {
+            cards: cards.into_iter().map(HeaderCard::new).collect(),
+            modified: true,
+        }
+    }
+
+    fn cards(&self) -> impl Iterator<Item = &Card> {
+        self.cards.iter().map(|entry| &entry.card)
+    }
+
+    /// The index of the first record of the first card matching `predicate`, which differs from
+    /// the card index after strings continued over CONTINUE records
+    fn record_index(&self, predicate: impl Fn(&Card) -> bool) -> Option<usize> {
+        let mut records = 0;
+        for entry in &self.cards {
+            if predicate(&entry.card) {
+                return Some(records);
+            }
+            records += entry.records.len().max(1);
+        }
+        None
+    }
+
     pub(crate) fn bytes_len(&self) -> usize {
-        let num_bytes = self.cards.len() * CARD_NUM_BYTES;
+        let num_bytes = self.records().len() * CARD_NUM_BYTES;
         let num_off_bytes = 2880 - (num_bytes % 2880);
         if num_off_bytes == 2880 {
             num_bytes
@@ -30,7 +140,7 @@ impl Header {
     }
 
     pub fn author(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Author { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -39,9 +149,15 @@ impl Header {
         })
     }
 
+    pub fn set_author(&mut self, value: &str) {
+        self.set_card(Card::Author {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::AUTHOR),
+        });
+    }
+
     pub fn bitpix(&self) -> Bitpix {
-        self.cards
-            .iter()
+        self.cards()
             .find_map(|card| {
                 if let Card::Bitpix { value, .. } = card {
                     Some(*value)
@@ -53,7 +169,7 @@ impl Header {
     }
 
     pub fn blank(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Blank { value, .. } = card {
                 Some(*value)
             } else {
@@ -62,8 +178,15 @@ impl Header {
         })
     }
 
+    pub fn set_blank(&mut self, value: i64) {
+        self.set_card(Card::Blank {
+            value,
+            comment: self.existing_comment(card_keys::BLANK),
+        });
+    }
+
     pub fn blocked(&self) -> Option<bool> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Blocked { value, .. } = card {
                 Some(*value)
             } else {
@@ -73,7 +196,7 @@ impl Header {
     }
 
     pub fn bscale(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::BScale { value, .. } = card {
                 Some(*value)
             } else {
@@ -82,8 +205,15 @@ impl Header {
         })
     }
 
+    pub fn set_bscale(&mut self, value: f64) {
+        self.set_card(Card::BScale {
+            value,
+            comment: self.existing_comment(card_keys::BSCALE),
+        });
+    }
+
     pub fn bunit(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::BUnit { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -92,8 +222,15 @@ impl Header {
         })
     }
 
+    pub fn set_bunit(&mut self, value: &str) {
+        self.set_card(Card::BUnit {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::BUNIT),
+        });
+    }
+
     pub fn bzero(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::BZero { value, .. } = card {
                 Some(*value)
             } else {
@@ -102,8 +239,39 @@ impl Header {
         })
     }
 
+    pub fn set_bzero(&mut self, value: f64) {
+        self.set_card(Card::BZero {
+            value,
+            comment: self.existing_comment(card_keys::BZERO),
+        });
+    }
+
+    /// Whether BZERO and BSCALE apply the standard integer offset for BITPIX, which means the data
+    /// holds unsigned 16, 32 or 64 bit integers, or signed bytes, see [`Bitpix::integer_offset`]
+    pub fn has_integer_offset(&self) -> bool {
+        self.bitpix().integer_offset().is_some()
+            && self.bzero() == self.bitpix().integer_offset()
+            && self.bscale().unwrap_or(1.0) == 1.0
+    }
+
+    /// Sets BZERO and BSCALE to the standard integer offset for BITPIX, or removes them when they
+    /// hold the offset of `previous`, the BITPIX the data was written with before
+    pub(crate) fn set_integer_offset(&mut self, enabled: bool, previous: Bitpix) {
+        if enabled && let Some(offset) = self.bitpix().integer_offset() {
+            self.set_bzero(offset);
+            self.set_bscale(1.0);
+        } else if previous.integer_offset().is_some()
+            && self.bzero() == previous.integer_offset()
+            && self.bscale().unwrap_or(1.0) == 1.0
+        {
+            self.cards
+                .retain(|entry| !matches!(entry.card, Card::BZero { .. } | Card::BScale { .. }));
+            self.modified = true;
+        }
+    }
+
     pub fn data_max(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::DataMax { value, .. } = card {
                 Some(*value)
             } else {
@@ -112,8 +280,15 @@ impl Header {
         })
     }
 
+    pub fn set_data_max(&mut self, value: f64) {
+        self.set_card(Card::DataMax {
+            value,
+            comment: self.existing_comment(card_keys::DATAMAX),
+        });
+    }
+
     pub fn data_min(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::DataMin { value, .. } = card {
                 Some(*value)
             } else {
@@ -122,8 +297,15 @@ impl Header {
         })
     }
 
+    pub fn set_data_min(&mut self, value: f64) {
+        self.set_card(Card::DataMin {
+            value,
+            comment: self.existing_comment(card_keys::DATAMIN),
+        });
+    }
+
     pub fn date(&self) -> Option<&DateTime<Utc>> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Date { value, .. } = card {
                 Some(value)
             } else {
@@ -132,8 +314,15 @@ impl Header {
         })
     }
 
+    pub fn set_date(&mut self, value: DateTime<Utc>) {
+        self.set_card(Card::Date {
+            value,
+            comment: self.existing_comment(card_keys::DATE),
+        });
+    }
+
     pub fn date_observed(&self) -> Option<&DateTime<Utc>> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::DateObserved { value, .. } = card {
                 Some(value)
             } else {
@@ -142,8 +331,15 @@ impl Header {
         })
     }
 
+    pub fn set_date_observed(&mut self, value: DateTime<Utc>) {
+        self.set_card(Card::DateObserved {
+            value,
+            comment: self.existing_comment(card_keys::DATE_OBS),
+        });
+    }
+
     pub fn epoch(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Epoch { value, .. } = card {
                 Some(*value)
             } else {
@@ -152,8 +348,15 @@ impl Header {
         })
     }
 
+    pub fn set_epoch(&mut self, value: f64) {
+        self.set_card(Card::Epoch {
+            value,
+            comment: self.existing_comment(card_keys::EPOCH),
+        });
+    }
+
     pub fn equinox(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Equinox { value, .. } = card {
                 Some(*value)
             } else {
@@ -162,8 +365,15 @@ impl Header {
         })
     }
 
+    pub fn set_equinox(&mut self, value: f64) {
+        self.set_card(Card::Equinox {
+            value,
+            comment: self.existing_comment(card_keys::EQUINOX),
+        });
+    }
+
     pub fn extend(&self) -> Option<bool> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Extend { value, .. } = card {
                 Some(*value)
             } else {
@@ -172,8 +382,15 @@ impl Header {
         })
     }
 
+    pub fn set_extend(&mut self, value: bool) {
+        self.set_card(Card::Extend {
+            value,
+            comment: self.existing_comment(card_keys::EXTEND),
+        });
+    }
+
     pub fn extension_level(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ExtensionLevel { value, .. } = card {
                 Some(*value)
             } else {
@@ -182,8 +399,15 @@ impl Header {
         })
     }
 
+    pub fn set_extension_level(&mut self, value: i64) {
+        self.set_card(Card::ExtensionLevel {
+            value,
+            comment: self.existing_comment(card_keys::EXTLEVEL),
+        });
+    }
+
     pub fn extension_name(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ExtensionName { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -192,8 +416,15 @@ impl Header {
         })
     }
 
+    pub fn set_extension_name(&mut self, value: &str) {
+        self.set_card(Card::ExtensionName {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::EXTNAME),
+        });
+    }
+
     pub fn extension_version(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ExtensionVersion { value, .. } = card {
                 Some(*value)
             } else {
@@ -202,8 +433,15 @@ impl Header {
         })
     }
 
+    pub fn set_extension_version(&mut self, value: i64) {
+        self.set_card(Card::ExtensionVersion {
+            value,
+            comment: self.existing_comment(card_keys::EXTVER),
+        });
+    }
+
     pub fn group_count(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::GroupCount { value, .. } = card {
                 Some(*value)
             } else {
@@ -213,7 +451,7 @@ impl Header {
     }
 
     pub fn groups(&self) -> Option<bool> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Groups { value, .. } = card {
                 Some(*value)
             } else {
@@ -223,7 +461,7 @@ impl Header {
     }
 
     pub fn instrument(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Instrument { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -232,9 +470,15 @@ impl Header {
         })
     }
 
+    pub fn set_instrument(&mut self, value: &str) {
+        self.set_card(Card::Instrument {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::INSTRUME),
+        });
+    }
+
     pub fn naxis(&self) -> i64 {
-        self.cards
-            .iter()
+        self.cards()
             .find_map(|card| {
                 if let Card::NAxis { value, .. } = card {
                     Some(*value)
@@ -246,7 +490,7 @@ impl Header {
     }
 
     pub fn object(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Object { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -255,8 +499,15 @@ impl Header {
         })
     }
 
+    pub fn set_object(&mut self, value: &str) {
+        self.set_card(Card::Object {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::OBJECT),
+        });
+    }
+
     pub fn observer(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Observer { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -265,8 +516,15 @@ impl Header {
         })
     }
 
+    pub fn set_observer(&mut self, value: &str) {
+        self.set_card(Card::Observer {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::OBSERVER),
+        });
+    }
+
     pub fn origin(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Origin { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -275,8 +533,15 @@ impl Header {
         })
     }
 
+    pub fn set_origin(&mut self, value: &str) {
+        self.set_card(Card::Origin {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::ORIGIN),
+        });
+    }
+
     pub fn pcount(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ParameterCount { value, .. } = card {
                 Some(*value)
             } else {
@@ -286,7 +551,7 @@ impl Header {
     }
 
     pub fn reference(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Reference { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -295,8 +560,15 @@ impl Header {
         })
     }
 
+    pub fn set_reference(&mut self, value: &str) {
+        self.set_card(Card::Reference {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::REFERENC),
+        });
+    }
+
     pub fn simple(&self) -> Option<bool> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Simple { value, .. } = card {
                 Some(*value)
             } else {
@@ -306,7 +578,7 @@ impl Header {
     }
 
     pub fn telescope(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Telescope { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -315,8 +587,15 @@ impl Header {
         })
     }
 
+    pub fn set_telescope(&mut self, value: &str) {
+        self.set_card(Card::Telescope {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::TELESCOP),
+        });
+    }
+
     pub fn table_fields(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableFields { value, .. } = card {
                 Some(*value)
             } else {
@@ -326,7 +605,7 @@ impl Header {
     }
 
     pub fn table_heap(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableHeap { value, .. } = card {
                 Some(*value)
             } else {
@@ -336,7 +615,7 @@ impl Header {
     }
 
     pub fn extension(&self) -> Option<ExtensionType> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Xtension { value, .. } = card {
                 Some(*value)
             } else {
@@ -346,7 +625,7 @@ impl Header {
     }
 
     pub fn focal_length(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::FocalLength { value, .. } = card {
                 Some(*value)
             } else {
@@ -355,8 +634,15 @@ impl Header {
         })
     }
 
+    pub fn set_focal_length(&mut self, value: f64) {
+        self.set_card(Card::FocalLength {
+            value,
+            comment: self.existing_comment(card_keys::FOCALLEN),
+        });
+    }
+
     pub fn exposure_time(&self) -> Option<core::time::Duration> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ExposureTime { value, .. } = card {
                 Some(*value)
             } else {
@@ -365,8 +651,15 @@ impl Header {
         })
     }
 
+    pub fn set_exposure_time(&mut self, value: core::time::Duration) {
+        self.set_card(Card::ExposureTime {
+            value,
+            comment: self.existing_comment(card_keys::EXPTIME),
+        });
+    }
+
     pub fn ccd_temperature(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::CCDTemperature { value, .. } = card {
                 Some(*value)
             } else {
@@ -375,8 +668,15 @@ impl Header {
         })
     }
 
+    pub fn set_ccd_temperature(&mut self, value: f64) {
+        self.set_card(Card::CCDTemperature {
+            value,
+            comment: self.existing_comment(card_keys::CCD_TEMP),
+        });
+    }
+
     pub fn bayer_pattern(&self) -> Option<BayerPattern> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::BayerPattern { value, .. } = card {
                 Some(*value)
             } else {
@@ -385,8 +685,15 @@ impl Header {
         })
     }
 
+    pub fn set_bayer_pattern(&mut self, value: BayerPattern) {
+        self.set_card(Card::BayerPattern {
+            value,
+            comment: self.existing_comment(card_keys::BAYERPAT),
+        });
+    }
+
     pub fn creator(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Creator { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -395,8 +702,15 @@ impl Header {
         })
     }
 
+    pub fn set_creator(&mut self, value: &str) {
+        self.set_card(Card::Creator {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::CREATOR),
+        });
+    }
+
     pub fn subframe_x_position_in_binned_pixels(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::SubframeXPositionInBinnedPixels { value, .. } = card {
                 Some(*value)
             } else {
@@ -405,8 +719,15 @@ impl Header {
         })
     }
 
+    pub fn set_subframe_x_position_in_binned_pixels(&mut self, value: i64) {
+        self.set_card(Card::SubframeXPositionInBinnedPixels {
+            value,
+            comment: self.existing_comment(card_keys::XORGSUBF),
+        });
+    }
+
     pub fn subframe_y_position_in_binned_pixels(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::SubframeYPositionInBinnedPixels { value, .. } = card {
                 Some(*value)
             } else {
@@ -415,8 +736,15 @@ impl Header {
         })
     }
 
+    pub fn set_subframe_y_position_in_binned_pixels(&mut self, value: i64) {
+        self.set_card(Card::SubframeYPositionInBinnedPixels {
+            value,
+            comment: self.existing_comment(card_keys::YORGSUBF),
+        });
+    }
+
     pub fn binned_pixels_x(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::BinnedPixelsX { value, .. } = card {
                 Some(*value)
             } else {
@@ -425,8 +753,15 @@ impl Header {
         })
     }
 
+    pub fn set_binned_pixels_x(&mut self, value: i64) {
+        self.set_card(Card::BinnedPixelsX {
+            value,
+            comment: self.existing_comment(card_keys::XBINNING),
+        });
+    }
+
     pub fn binned_pixels_y(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::BinnedPixelsY { value, .. } = card {
                 Some(*value)
             } else {
@@ -435,8 +770,15 @@ impl Header {
         })
     }
 
+    pub fn set_binned_pixels_y(&mut self, value: i64) {
+        self.set_card(Card::BinnedPixelsY {
+            value,
+            comment: self.existing_comment(card_keys::YBINNING),
+        });
+    }
+
     pub fn ccd_binned_pixels_x(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::CCDBinnedPixelsX { value, .. } = card {
                 Some(*value)
             } else {
@@ -445,8 +787,15 @@ impl Header {
         })
     }
 
+    pub fn set_ccd_binned_pixels_x(&mut self, value: i64) {
+        self.set_card(Card::CCDBinnedPixelsX {
+            value,
+            comment: self.existing_comment(card_keys::CCDXBIN),
+        });
+    }
+
     pub fn ccd_binned_pixels_y(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::CCDBinnedPixelsY { value, .. } = card {
                 Some(*value)
             } else {
@@ -455,8 +804,15 @@ impl Header {
         })
     }
 
+    pub fn set_ccd_binned_pixels_y(&mut self, value: i64) {
+        self.set_card(Card::CCDBinnedPixelsY {
+            value,
+            comment: self.existing_comment(card_keys::CCDYBIN),
+        });
+    }
+
     pub fn pixel_size_x_with_binning_in_microns(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::PixelSizeXWithBinningInMicrons { value, .. } = card {
                 Some(*value)
             } else {
@@ -465,8 +821,15 @@ impl Header {
         })
     }
 
+    pub fn set_pixel_size_x_with_binning_in_microns(&mut self, value: f64) {
+        self.set_card(Card::PixelSizeXWithBinningInMicrons {
+            value,
+            comment: self.existing_comment(card_keys::XPIXSZ),
+        });
+    }
+
     pub fn pixel_size_y_with_binning_in_microns(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::PixelSizeYWithBinningInMicrons { value, .. } = card {
                 Some(*value)
             } else {
@@ -475,8 +838,15 @@ impl Header {
         })
     }
 
+    pub fn set_pixel_size_y_with_binning_in_microns(&mut self, value: f64) {
+        self.set_card(Card::PixelSizeYWithBinningInMicrons {
+            value,
+            comment: self.existing_comment(card_keys::YPIXSZ),
+        });
+    }
+
     pub fn image_type(&self) -> Option<&ImageType> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ImageType { value, .. } = card {
                 Some(value)
             } else {
@@ -485,8 +855,15 @@ impl Header {
         })
     }
 
+    pub fn set_image_type(&mut self, value: ImageType) {
+        self.set_card(Card::ImageType {
+            value,
+            comment: self.existing_comment(card_keys::IMAGETYP),
+        });
+    }
+
     pub fn exposure(&self) -> Option<core::time::Duration> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Exposure { value, .. } = card {
                 Some(*value)
             } else {
@@ -495,8 +872,15 @@ impl Header {
         })
     }
 
+    pub fn set_exposure(&mut self, value: core::time::Duration) {
+        self.set_card(Card::Exposure {
+            value,
+            comment: self.existing_comment(card_keys::EXPOSURE),
+        });
+    }
+
     pub fn ra(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Ra { value, .. } = card {
                 Some(*value)
             } else {
@@ -505,8 +889,15 @@ impl Header {
         })
     }
 
+    pub fn set_ra(&mut self, value: f64) {
+        self.set_card(Card::Ra {
+            value,
+            comment: self.existing_comment(card_keys::RA),
+        });
+    }
+
     pub fn dec(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::Dec { value, .. } = card {
                 Some(*value)
             } else {
@@ -515,8 +906,15 @@ impl Header {
         })
     }
 
+    pub fn set_dec(&mut self, value: f64) {
+        self.set_card(Card::Dec {
+            value,
+            comment: self.existing_comment(card_keys::DEC),
+        });
+    }
+
     pub fn guide_cam(&self) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::GuideCam { value, .. } = card {
                 Some(value.as_str())
             } else {
@@ -525,8 +923,15 @@ impl Header {
         })
     }
 
+    pub fn set_guide_cam(&mut self, value: &str) {
+        self.set_card(Card::GuideCam {
+            value: value.into(),
+            comment: self.existing_comment(card_keys::GUIDECAM),
+        });
+    }
+
     pub fn focus_position(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::FocusPosition { value, .. } = card {
                 Some(*value)
             } else {
@@ -535,8 +940,15 @@ impl Header {
         })
     }
 
+    pub fn set_focus_position(&mut self, value: i64) {
+        self.set_card(Card::FocusPosition {
+            value,
+            comment: self.existing_comment(card_keys::FOCUSPOS),
+        });
+    }
+
     pub fn site_longitude(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::SiteLongitude { value, .. } = card {
                 Some(*value)
             } else {
@@ -545,8 +957,15 @@ impl Header {
         })
     }
 
+    pub fn set_site_longitude(&mut self, value: f64) {
+        self.set_card(Card::SiteLongitude {
+            value,
+            comment: self.existing_comment(card_keys::SITELONG),
+        });
+    }
+
     pub fn site_latitude(&self) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::SiteLatitude { value, .. } = card {
                 Some(*value)
             } else {
@@ -555,8 +974,15 @@ impl Header {
         })
     }
 
+    pub fn set_site_latitude(&mut self, value: f64) {
+        self.set_card(Card::SiteLatitude {
+            value,
+            comment: self.existing_comment(card_keys::SITELAT),
+        });
+    }
+
     pub fn image_width(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ImageWidth { value, .. } = card {
                 Some(*value)
             } else {
@@ -565,8 +991,15 @@ impl Header {
         })
     }
 
+    pub fn set_image_width(&mut self, value: i64) {
+        self.set_card(Card::ImageWidth {
+            value,
+            comment: self.existing_comment(card_keys::IMAGEW),
+        });
+    }
+
     pub fn image_height(&self) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ImageHeight { value, .. } = card {
                 Some(*value)
             } else {
@@ -575,8 +1008,15 @@ impl Header {
         })
     }
 
+    pub fn set_image_height(&mut self, value: i64) {
+        self.set_card(Card::ImageHeight {
+            value,
+            comment: self.existing_comment(card_keys::IMAGEH),
+        });
+    }
+
     pub fn coordinate_delta(&self, index: usize) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::CoordinateDeltaN {
                 value, index: idx, ..
             } = card
@@ -589,8 +1029,17 @@ impl Header {
         })
     }
 
+    pub fn set_coordinate_delta(&mut self, index: usize, value: f64) {
+        let key = format!("{}{}", card_keys::PREFIX_CDELT_N, index + 1);
+        self.set_card(Card::CoordinateDeltaN {
+            index,
+            value,
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn coordinate_rotation(&self, index: usize) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::CoordinateRotationN {
                 value, index: idx, ..
             } = card
@@ -603,8 +1052,17 @@ impl Header {
         })
     }
 
+    pub fn set_coordinate_rotation(&mut self, index: usize, value: f64) {
+        let key = format!("{}{}", card_keys::PREFIX_CROTA_N, index + 1);
+        self.set_card(Card::CoordinateRotationN {
+            index,
+            value,
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn coordinate_reference_pixel(&self, index: usize) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::CoordinateReferencePixelN {
                 value, index: idx, ..
             } = card
@@ -617,8 +1075,17 @@ impl Header {
         })
     }
 
+    pub fn set_coordinate_reference_pixel(&mut self, index: usize, value: f64) {
+        let key = format!("{}{}", card_keys::PREFIX_CRPIX_N, index + 1);
+        self.set_card(Card::CoordinateReferencePixelN {
+            index,
+            value,
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn coordinate_value_at_pixel(&self, index: usize) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::CoordinateValueAtPixelN {
                 value, index: idx, ..
             } = card
@@ -631,8 +1098,17 @@ impl Header {
         })
     }
 
+    pub fn set_coordinate_value_at_pixel(&mut self, index: usize, value: f64) {
+        let key = format!("{}{}", card_keys::PREFIX_CRVAL_N, index + 1);
+        self.set_card(Card::CoordinateValueAtPixelN {
+            index,
+            value,
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn coordinate_axis_name(&self, index: usize) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::CoordinateAxisNameN {
                 value, index: idx, ..
             } = card
@@ -645,8 +1121,24 @@ impl Header {
         })
     }
 
+    pub fn set_coordinate_axis_name(&mut self, index: usize, value: &str) {
+        let key = format!("{}{}", card_keys::PREFIX_CTYPE_N, index + 1);
+        self.set_card(Card::CoordinateAxisNameN {
+            index,
+            value: value.into(),
+            comment: self.existing_comment(&key),
+        });
+    }
+
+    /// The length of every data axis, NAXIS1 first. Missing NAXISn keywords count as 0.
+    pub fn data_shape(&self) -> Vec<usize> {
+        (0..self.naxis().max(0) as usize)
+            .map(|index| self.naxis_n(index).unwrap_or(0).max(0) as usize)
+            .collect()
+    }
+
     pub fn naxis_n(&self, index: usize) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::NAxisN {
                 value, index: idx, ..
             } = card
@@ -660,7 +1152,7 @@ impl Header {
     }
 
     pub fn parameter_scaling_factor(&self, index: usize) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ParameterScalingFactorN {
                 value, index: idx, ..
             } = card
@@ -673,8 +1165,17 @@ impl Header {
         })
     }
 
+    pub fn set_parameter_scaling_factor(&mut self, index: usize, value: f64) {
+        let key = format!("{}{}", card_keys::PREFIX_PSCAL_N, index + 1);
+        self.set_card(Card::ParameterScalingFactorN {
+            index,
+            value,
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn parameter_type(&self, index: usize) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ParameterTypeN {
                 value, index: idx, ..
             } = card
@@ -687,8 +1188,17 @@ impl Header {
         })
     }
 
+    pub fn set_parameter_type(&mut self, index: usize, value: &str) {
+        let key = format!("{}{}", card_keys::PREFIX_PTYPE_N, index + 1);
+        self.set_card(Card::ParameterTypeN {
+            index,
+            value: value.into(),
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn parameter_scaling_zero_point(&self, index: usize) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::ParameterScalingZeroPointN {
                 value, index: idx, ..
             } = card
@@ -701,8 +1211,17 @@ impl Header {
         })
     }
 
+    pub fn set_parameter_scaling_zero_point(&mut self, index: usize, value: f64) {
+        let key = format!("{}{}", card_keys::PREFIX_PZERO_N, index + 1);
+        self.set_card(Card::ParameterScalingZeroPointN {
+            index,
+            value,
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn table_column(&self, index: usize) -> Option<i64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableColumnN {
                 value, index: idx, ..
             } = card
@@ -716,7 +1235,7 @@ impl Header {
     }
 
     pub fn table_dimensions(&self, index: usize) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableDimensionsN {
                 value, index: idx, ..
             } = card
@@ -729,8 +1248,17 @@ impl Header {
         })
     }
 
+    pub fn set_table_dimensions(&mut self, index: usize, value: &str) {
+        let key = format!("{}{}", card_keys::PREFIX_TDIM_N, index + 1);
+        self.set_card(Card::TableDimensionsN {
+            index,
+            value: value.into(),
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn table_display_format(&self, index: usize) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableDisplayFormatN {
                 value, index: idx, ..
             } = card
@@ -743,8 +1271,17 @@ impl Header {
         })
     }
 
+    pub fn set_table_display_format(&mut self, index: usize, value: &str) {
+        let key = format!("{}{}", card_keys::PREFIX_TDISP_N, index + 1);
+        self.set_card(Card::TableDisplayFormatN {
+            index,
+            value: value.into(),
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn table_null_value(&self, index: usize) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableNullValueN {
                 value, index: idx, ..
             } = card
@@ -757,8 +1294,17 @@ impl Header {
         })
     }
 
+    pub fn set_table_null_value(&mut self, index: usize, value: &str) {
+        let key = format!("{}{}", card_keys::PREFIX_TNULL_N, index + 1);
+        self.set_card(Card::TableNullValueN {
+            index,
+            value: value.into(),
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn table_scaling_factor(&self, index: usize) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableScalingFactorN {
                 value, index: idx, ..
             } = card
@@ -771,8 +1317,17 @@ impl Header {
         })
     }
 
+    pub fn set_table_scaling_factor(&mut self, index: usize, value: f64) {
+        let key = format!("{}{}", card_keys::PREFIX_TSCAL_N, index + 1);
+        self.set_card(Card::TableScalingFactorN {
+            index,
+            value,
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn table_column_type(&self, index: usize) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableTypeN {
                 value, index: idx, ..
             } = card
@@ -785,8 +1340,17 @@ impl Header {
         })
     }
 
+    pub fn set_table_column_type(&mut self, index: usize, value: &str) {
+        let key = format!("{}{}", card_keys::PREFIX_TTYPE_N, index + 1);
+        self.set_card(Card::TableTypeN {
+            index,
+            value: value.into(),
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn table_column_format(&self, index: usize) -> Option<TableColumnFormat> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableFormatN {
                 value, index: idx, ..
             } = card
@@ -800,7 +1364,7 @@ impl Header {
     }
 
     pub fn table_unit(&self, index: usize) -> Option<&str> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableUnitN {
                 value, index: idx, ..
             } = card
@@ -813,8 +1377,17 @@ impl Header {
         })
     }
 
+    pub fn set_table_unit(&mut self, index: usize, value: &str) {
+        let key = format!("{}{}", card_keys::PREFIX_TUNIT_N, index + 1);
+        self.set_card(Card::TableUnitN {
+            index,
+            value: value.into(),
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub fn table_scaling_zero_point(&self, index: usize) -> Option<f64> {
-        self.cards.iter().find_map(|card| {
+        self.cards().find_map(|card| {
             if let Card::TableScalingZeroPointN {
                 value, index: idx, ..
             } = card
@@ -827,6 +1400,15 @@ impl Header {
         })
     }
 
+    pub fn set_table_scaling_zero_point(&mut self, index: usize, value: f64) {
+        let key = format!("{}{}", card_keys::PREFIX_TZERO_N, index + 1);
+        self.set_card(Card::TableScalingZeroPointN {
+            index,
+            value,
+            comment: self.existing_comment(&key),
+        });
+    }
+
     pub(crate) fn data_block_len(&self) -> usize {
         let data_size = self.data_bytes_len();
 
@@ -840,23 +1422,71 @@ impl Header {
 
     pub(crate) fn data_bytes_len(&self) -> usize {
         let item_size = self.bitpix().byte_size();
-        let number_of_axis = self.naxis();
+        let number_of_axis = self.naxis() as usize;
 
         if number_of_axis == 0 {
             return 0;
         }
 
-        let mut bytes = item_size;
-        for axis in 0..number_of_axis {
-            let axis = self.naxis_n((axis) as usize).unwrap() as usize;
-            bytes *= axis;
+        // Random groups do not count NAXIS1, it is always 0, cf. FITSv4, section 6
+        let first_axis = if self.groups() == Some(true) { 1 } else { 0 };
+
+        let mut items = 1;
+        for axis in first_axis..number_of_axis {
+            items *= self.naxis_n(axis).unwrap_or(0) as usize;
         }
-        bytes
+
+        // Binary tables and random groups may have additional data after the main array, cf.
+        // FITSv4, section 4.4.1.1
+        let parameter_count = self.pcount().unwrap_or(0) as usize;
+        let group_count = self.group_count().unwrap_or(1) as usize;
+
+        item_size * group_count * (parameter_count + items)
+    }
+
+    /// Replaces the cards describing the data array, BITPIX, NAXIS and NAXISn, keeping them in the
+    /// mandatory order directly after SIMPLE or XTENSION
+    pub(crate) fn set_data_layout(&mut self, bitpix: Bitpix, axes: &[u64]) {
+        self.cards.retain(|entry| {
+            !matches!(
+                entry.card,
+                Card::Bitpix { .. } | Card::NAxis { .. } | Card::NAxisN { .. }
+            )
+        });
+
+        let mut position = self
+            .cards()
+            .position(|card| matches!(card, Card::Simple { .. } | Card::Xtension { .. }))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+
+        let mut layout = vec![
+            Card::Bitpix {
+                value: bitpix,
+                comment: Some("number of bits per data pixel".into()),
+            },
+            Card::NAxis {
+                value: axes.len() as i64,
+                comment: Some("number of data axes".into()),
+            },
+        ];
+        for (index, axis) in axes.iter().enumerate() {
+            layout.push(Card::NAxisN {
+                index,
+                value: *axis as i64,
+                comment: Some(format!("length of data axis {}", index + 1)),
+            });
+        }
+
+        for card in layout {
+            self.cards.insert(position, HeaderCard::new(card));
+            position += 1;
+        }
+        self.modified = true;
     }
 
     pub fn raw_card(&self, key: &str) -> Vec<Value> {
-        self.cards
-            .iter()
+        self.cards()
             .filter_map(|card| {
                 if key == card.key() {
                     Some(Value::from(card))
@@ -867,74 +1497,720 @@ impl Header {
             .collect()
     }
 
-    pub(crate) fn from_reader(
-        reader: &mut Box<dyn ReadSeek>,
-    ) -> Result<Option<Self>, Box<dyn Error + Send + Sync>> {
-        let cards = Self::read_all_cards(reader)?;
+    /// The bytes the first card with the given keyword was read from, several 80 character records
+    /// for continued strings. Returns `None` for cards that were added after reading.
+    pub fn original_record(&self, key: &str) -> Option<&[u8]> {
+        self.cards
+            .iter()
+            .find(|entry| entry.card.key() == key)
+            .map(|entry| entry.records.as_flattened())
+            .filter(|records| !records.is_empty())
+    }
 
-        if let Some(Card::End) = cards.last() {
-            Ok(Some(Self { cards }))
-        } else {
-            Ok(None)
+    /// Whether the first card with the given keyword was added or changed since the header was read
+    pub fn is_modified(&self, key: &str) -> bool {
+        self.cards
+            .iter()
+            .find(|entry| entry.card.key() == key)
+            .is_some_and(|entry| entry.modified)
+    }
+
+    /// Iterates over all cards in the order they appear in the header, without the END card
+    pub fn iter(&self) -> impl Iterator<Item = &Card> {
+        self.cards().filter(|card| **card != Card::End)
+    }
+
+    /// Finds all cards whose keyword matches a CFITSIO style pattern, where `?` matches any
+    /// character, `*` any sequence of characters and `#` any sequence of digits, e.g. `TTYPE#`
+    pub fn find(&self, pattern: &str) -> impl Iterator<Item = &Card> {
+        self.find_matching(move |key| matches_pattern(pattern.as_bytes(), key.as_bytes()))
+    }
+
+    /// Finds all cards whose keyword is accepted by `predicate`
+    pub fn find_matching<F>(&self, predicate: F) -> impl Iterator<Item = &Card>
+    where
+        F: Fn(&str) -> bool,
+    {
+        self.iter().filter(move |card| predicate(&card.key()))
+    }
+
+    /// Finds all cards whose keyword matches one of the `include` patterns and none of the
+    /// `exclude` patterns, like `ffgnxk` in CFITSIO. See [`Header::find`] for the pattern syntax.
+    pub fn select<'a>(
+        &'a self,
+        include: &'a [&str],
+        exclude: &'a [&str],
+    ) -> impl Iterator<Item = &'a Card> {
+        self.find_matching(move |key| {
+            let matches = |pattern: &&str| matches_pattern(pattern.as_bytes(), key.as_bytes());
+            include.iter().any(matches) && !exclude.iter().any(matches)
+        })
+    }
+
+    /// Lists the keywords that were added, removed or changed in `other` compared to this header
+    pub fn diff(&self, other: &Header) -> Vec<KeywordDiff> {
+        diff(self, other, &DiffOptions::default())
+    }
+
+    /// Compares two headers like [`Header::diff`], ignoring keywords or small numeric differences
+    pub fn diff_with(&self, other: &Header, options: &DiffOptions) -> Vec<KeywordDiff> {
+        diff(self, other, options)
+    }
+
+    /// Iterates over the text of all HISTORY cards, in order
+    pub fn history(&self) -> impl Iterator<Item = &str> {
+        self.cards().filter_map(|card| match card {
+            Card::History(text) => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Iterates over the text of all COMMENT cards, in order
+    pub fn comments(&self) -> impl Iterator<Item = &str> {
+        self.cards().filter_map(|card| match card {
+            Card::Comment(text) => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Appends HISTORY cards at the end of the header. Text that does not fit on a single card is
+    /// wrapped over several cards, breaking between words where possible.
+    pub fn add_history(&mut self, text: &str) {
+        for line in wrap_text(text, COMMENTARY_NUM_CHARS) {
+            self.add_card(Card::History(line));
         }
     }
 
-    pub(crate) fn validate_primary(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
-        if self.simple().is_none() {
-            return Err("This is not a valid fits file. Card SIMPLE is missing".into());
+    /// Appends COMMENT cards at the end of the header, see [`Header::add_history`]
+    pub fn add_comment(&mut self, text: &str) {
+        for line in wrap_text(text, COMMENTARY_NUM_CHARS) {
+            self.add_card(Card::Comment(line));
         }
-        if let Some(false) = self.simple() {
-            return Err(
-                "This is not a valid fits file. It must contain card simple with value true".into(),
-            );
+    }
+
+    /// Looks up the value of any keyword, converted to the requested type. Returns `None` when the
+    /// keyword is missing or has no value, and an error when the value has a different type.
+    pub fn get<T: FromHeaderValue>(&self, key: &str) -> Result<Option<T>> {
+        let value = match self.cards().find(|card| card.key() == key) {
+            Some(card) => Value::from(card),
+            None => return Ok(None),
+        };
+        if value == Value::Undefined {
+            return Ok(None);
         }
 
+        T::from_header_value(&value)
+            .map(Some)
+            .map_err(|err| match err {
+                Error::InvalidValue { message, .. } => Error::InvalidValue {
+                    keyword: key.into(),
+                    message,
+                },
+                err => err,
+            })
+    }
+
+    /// Looks up a HIERARCH keyword by its full name, either dot or space separated, with or without
+    /// the leading `HIERARCH`
+    pub fn hierarch(&self, name: &str) -> Option<&Value> {
+        let name = hierarch_name(name);
+        self.cards().find_map(|card| match card {
+            Card::Hierarch {
+                name: card_name,
+                value,
+            } if *card_name == name => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Sets a HIERARCH keyword, see [`Header::set`]
+    pub fn set_hierarch(
+        &mut self,
+        name: &str,
+        value: impl Into<Value>,
+        comment: Option<&str>,
+    ) -> Result<()> {
+        let name = hierarch_name(name);
+        if name.is_empty() {
+            return Err(Error::InvalidKeyword {
+                keyword: "HIERARCH".into(),
+                message: "HIERARCH keywords need a name".into(),
+            });
+        }
+        self.set(&format!("HIERARCH {}", name), value, comment)
+    }
+
+    /// Sets a keyword to the given value, replacing the first card with the same keyword or adding
+    /// a new card before END. The existing comment is kept when no comment is given.
+    pub fn set(&mut self, key: &str, value: impl Into<Value>, comment: Option<&str>) -> Result<()> {
+        if is_structural_key(key) {
+            return Err(Error::InvalidKeyword {
+                keyword: key.into(),
+                message: "describes the data layout and can not be set directly".into(),
+            });
+        }
+
+        let comment = match comment {
+            Some(comment) => Some(comment.into()),
+            None => self.existing_comment(key),
+        };
+        let card = Card::new(key, value.into().with_comment(comment))?;
+        self.set_card(card);
         Ok(())
     }
 
-    pub(crate) fn validate_extension(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
-        if self.extension_name().is_none() {
-            return Err("This is not a valid fits extension. Card XTENSION is missing".into());
+    /// Removes all cards with the given keyword, returning whether any card was removed
+    pub fn remove(&mut self, key: &str) -> Result<bool> {
+        if is_structural_key(key) || key == card_keys::END {
+            return Err(Error::InvalidKeyword {
+                keyword: key.into(),
+                message: "is mandatory and can not be removed".into(),
+            });
+        }
+
+        let len = self.cards.len();
+        self.cards.retain(|entry| entry.card.key() != key);
+        if self.cards.len() == len {
+            return Ok(false);
+        }
+        self.modified = true;
+        Ok(true)
+    }
+
+    /// Inserts a card directly after the first card with the given keyword. Cards can not be placed
+    /// inside the mandatory keywords at the start of the header or after END.
+    pub fn insert_after(&mut self, key: &str, card: Card) -> Result<()> {
+        if matches!(card, Card::End) || is_structural_key(&card.key()) {
+            return Err(Error::InvalidKeyword {
+                keyword: card.key(),
+                message: "describes the data layout and can not be inserted".into(),
+            });
+        }
+
+        let position = self
+            .cards()
+            .position(|existing| existing.key() == key)
+            .ok_or_else(|| Error::KeywordNotFound(key.into()))?
+            + 1;
+
+        let mandatory_len = self
+            .cards()
+            .take_while(|existing| is_structural_key(&existing.key()))
+            .count();
+        if position < mandatory_len {
+            return Err(Error::InvalidKeyword {
+                keyword: card.key(),
+                message: "would break the order of the mandatory keywords".into(),
+            });
+        }
+        if matches!(self.cards().nth(position - 1), Some(Card::End)) {
+            return Err(Error::InvalidKeyword {
+                keyword: card.key(),
+                message: "can not be inserted after END".into(),
+            });
         }
 
+        self.cards.insert(position, HeaderCard::new(card));
+        self.modified = true;
         Ok(())
     }
 
-    fn read_all_cards(
+    fn existing_comment(&self, key: &str) -> Option<String> {
+        self.cards()
+            .find(|card| card.key() == key)
+            .map(|card| Value::from(card).comment_to_string())
+            .filter(|comment| !comment.is_empty())
+    }
+
+    fn set_card(&mut self, card: Card) {
+        let key = card.key();
+        if let Some(existing) = self
+            .cards
+            .iter_mut()
+            .find(|existing| existing.card.key() == key)
+        {
+            if existing.card == card {
+                return;
+            }
+            existing.card = card;
+            existing.modified = true;
+            self.modified = true;
+        } else {
+            self.add_card(card);
+        }
+    }
+
+    /// Adds a card at the end of the header, before END
+    fn add_card(&mut self, card: Card) {
+        if let Some(end) = self
+            .cards
+            .iter()
+            .position(|existing| matches!(existing.card, Card::End))
+        {
+            self.cards.insert(end, HeaderCard::new(card));
+        } else {
+            self.cards.push(HeaderCard::new(card));
+        }
+        self.modified = true;
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn from_reader(
         reader: &mut Box<dyn ReadSeek>,
-    ) -> Result<Vec<Card>, Box<dyn Error + Send + Sync>> {
-        let mut buffer = [0_u8; 2880 * 4];
+        hdu: usize,
+        options: &OpenOptions,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Option<Self>> {
+        let raw = Self::read_all_records(reader)?;
+        Self::from_u8(&raw, hdu, options, warnings)
+    }
+
+    /// Parses the header at the start of `data`. Cards that can not be parsed are kept as raw text
+    /// in lenient mode.
+    pub(crate) fn from_u8(
+        data: &[u8],
+        hdu: usize,
+        options: &OpenOptions,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<Option<Self>> {
         let mut cards = vec![];
 
-        'outer: loop {
-            let bytes_read = reader.read(&mut buffer)?;
-            if bytes_read == 0 {
+        for (index, record) in data.as_chunks::<CARD_NUM_BYTES>().0.iter().enumerate() {
+            let card = match Card::try_from(record) {
+                Ok(card) => card,
+                Err(err) => {
+                    let warning = ParseWarning {
+                        hdu,
+                        card: Some(index),
+                        keyword: String::from_utf8_lossy(record[..8].trim_ascii()).into_owned(),
+                        problem: match err {
+                            Error::InvalidCard { message, .. }
+                            | Error::InvalidValue { message, .. } => message,
+                            err => err.to_string(),
+                        },
+                    };
+                    // In strict mode the card itself is the error
+                    options.report(warnings, warning).map_err(|err| match err {
+                        Error::InvalidHeader(warning) => Error::InvalidCard {
+                            keyword: warning.keyword,
+                            message: warning.problem,
+                            position: Some(CardPosition { hdu, card: index }),
+                        },
+                        err => err,
+                    })?;
+                    Card::Undefined(String::from_utf8_lossy(record).into_owned())
+                }
+            };
+            let is_end = card == Card::End;
+            cards.push(HeaderCard::read(card, vec![*record]));
+            if is_end {
+                return Ok(Some(Self {
+                    cards: join_continuations(cards),
+                    modified: false,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Serializes this header into 80 character records, ending with the END record and padded
+    /// with blanks to a whole number of 2880 byte FITS blocks. A header that has not been changed
+    /// since it was read is written back exactly as it was read.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.records().concat();
+
+        let padding = (BLOCK_NUM_BYTES - bytes.len() % BLOCK_NUM_BYTES) % BLOCK_NUM_BYTES;
+        bytes.resize(bytes.len() + padding, b' ');
+        bytes
+    }
+
+    /// Parses a detached header, like the `.head` files written by SCAMP, with one card per line.
+    /// Lines may be shorter than 80 characters and the END card may be left out. The header is not
+    /// checked for mandatory keywords, as such files often only hold a few keywords.
+    pub fn from_text(text: &str) -> Result<Self> {
+        let mut data = Vec::with_capacity(text.len());
+        for line in text.lines() {
+            let line = line.trim_end();
+            if line.len() > CARD_NUM_BYTES {
+                return Err(Error::InvalidCard {
+                    keyword: line.get(..8).unwrap_or(line).trim_end().into(),
+                    message: "is longer than 80 characters".into(),
+                    position: Some(CardPosition {
+                        hdu: 0,
+                        card: data.len() / CARD_NUM_BYTES,
+                    }),
+                });
+            }
+            data.extend_from_slice(line.as_bytes());
+            data.resize(data.len() + CARD_NUM_BYTES - line.len(), b' ');
+            if line == card_keys::END {
                 break;
             }
-            let mut cursor = Cursor::new(&buffer[..bytes_read]);
-            while let Some(card) = Self::read_next_card(&mut cursor)? {
-                cards.push(card.clone());
-                if card == Card::End {
-                    break 'outer;
-                }
+        }
+        if !data.ends_with(&Card::End.to_record()) {
+            data.extend_from_slice(&Card::End.to_record());
+        }
+
+        let header = Self::from_u8(
+            data.as_slice(),
+            0,
+            OpenOptions::new().strict(true),
+            &mut vec![],
+        )?;
+        Ok(header.expect("header text ends with an END card"))
+    }
+
+    /// Formats this header as text with one card per line, without trailing blanks
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for record in self.records() {
+            text.push_str(String::from_utf8_lossy(&record).trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Copies all cards of `other` into this header, replacing cards with the same keyword. COMMENT
+    /// and HISTORY cards are added after the existing ones. Keywords describing the data layout
+    /// of `other`, like BITPIX and NAXISn, are left out.
+    pub fn merge(&mut self, other: &Header) {
+        for card in other.iter() {
+            match card {
+                Card::Comment(_) | Card::History(_) | Card::Space => self.add_card(card.clone()),
+                card if is_structural_key(&card.key()) => {}
+                card => self.set_card(card.clone()),
             }
         }
-        Ok(cards)
     }
 
-    fn read_next_card(
-        reader: &mut Cursor<&[u8]>,
-    ) -> Result<Option<Card>, Box<dyn Error + Send + Sync>> {
-        let mut buf = [0_u8; 80];
-        let read_bytes = reader.read(&mut buf)?;
+    /// Reads the keywords of this header into a struct, see [`crate::header::from_header`]
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        crate::header::from_header(self)
+    }
 
-        if read_bytes == 0 {
-            return Ok(None);
+    /// Sets a card for every field of a struct, see [`crate::header::to_cards`]. Existing cards
+    /// keep their comments, and keywords describing the data layout are rejected.
+    #[cfg(feature = "serde")]
+    pub fn serialize<T: serde::Serialize>(&mut self, value: &T) -> Result<()> {
+        let cards = crate::header::to_cards(value)?;
+        if let Some(card) = cards.iter().find(|card| is_structural_key(&card.key())) {
+            return Err(Error::InvalidKeyword {
+                keyword: card.key(),
+                message: "describes the data layout and can not be set directly".into(),
+            });
+        }
+
+        for card in cards {
+            self.set(&card.key(), card.value(), None)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the CHECKSUM and DATASUM keywords against this header and the given data unit, which
+    /// must be padded to whole FITS blocks
+    pub(crate) fn verify_checksum(&self, data: &[u8]) -> ChecksumVerification {
+        let datasum = ones_complement_sum(data, 0);
+
+        let data = match self.get::<String>(card_keys::DATASUM) {
+            Ok(Some(value)) if value.trim().parse::<u32>() == Ok(datasum) => ChecksumStatus::Valid,
+            Ok(None) => ChecksumStatus::Missing,
+            _ => ChecksumStatus::Invalid,
+        };
+        let header = if self.cards().any(|card| card.key() == card_keys::CHECKSUM) {
+            // A correct CHECKSUM makes the whole HDU sum to negative zero
+            match ones_complement_sum(&self.to_bytes(), datasum) {
+                0 | u32::MAX => ChecksumStatus::Valid,
+                _ => ChecksumStatus::Invalid,
+            }
+        } else {
+            ChecksumStatus::Missing
+        };
+
+        ChecksumVerification { header, data }
+    }
+
+    /// Updates the CHECKSUM and DATASUM keywords for the given data unit, padded to whole FITS
+    /// blocks. Headers that were read without checksums and not changed since are left as they
+    /// are, so that they are still written back byte for byte.
+    pub(crate) fn refresh_checksum(&mut self, data: &[u8]) {
+        let verification = self.verify_checksum(data);
+        let missing = verification.header == ChecksumStatus::Missing
+            && verification.data == ChecksumStatus::Missing;
+        if verification.is_valid() || (missing && !self.modified) {
+            return;
+        }
+
+        let datasum = ones_complement_sum(data, 0);
+        self.set_card(Card::Value {
+            name: card_keys::CHECKSUM.into(),
+            value: Value::String {
+                value: "0000000000000000".into(),
+                comment: Some("HDU checksum".into()),
+            },
+        });
+        self.set_card(Card::Value {
+            name: card_keys::DATASUM.into(),
+            value: Value::String {
+                value: format!("{}", datasum),
+                comment: Some("data unit checksum".into()),
+            },
+        });
+
+        let checksum = encode_checksum(ones_complement_sum(&self.to_bytes(), datasum));
+        self.set_card(Card::Value {
+            name: card_keys::CHECKSUM.into(),
+            value: Value::String {
+                value: checksum,
+                comment: Some("HDU checksum".into()),
+            },
+        });
+    }
+
+    /// Formats all cards as header records. A LONGSTRN card is added after the mandatory keywords
+    /// when a string had to be continued and the header does not declare the convention yet.
+    fn records(&self) -> Vec<[u8; CARD_NUM_BYTES]> {
+        let mut records = Vec::with_capacity(self.cards.len());
+        let mut continued = false;
+        for entry in &self.cards {
+            let card_records = entry.to_records();
+            continued |= entry.modified && card_records.len() > 1;
+            records.extend(card_records);
+        }
+
+        if continued && !self.cards().any(|card| card.key() == card_keys::LONGSTRN) {
+            let position = self
+                .cards()
+                .take_while(|card| is_structural_key(&card.key()))
+                .count();
+            let card = Card::Value {
+                name: card_keys::LONGSTRN.into(),
+                value: Value::String {
+                    value: "OGIP 1.0".into(),
+                    comment: Some("The OGIP long string convention may be used".into()),
+                },
+            };
+            records.insert(position, card.to_record());
+        }
+        records
+    }
+
+    /// Checks the mandatory keywords of the header of HDU `hdu`, cf. FITSv4, section 4.4.1.
+    /// Missing or invalid keywords describing the size of the data unit are always an error, other
+    /// problems are reported through `options`.
+    pub(crate) fn validate(
+        &self,
+        hdu: usize,
+        options: &OpenOptions,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> Result<()> {
+        let extension = if hdu == 0 {
+            match self.simple() {
+                None => return Err(missing_keyword(hdu, card_keys::SIMPLE)),
+                Some(false) => {
+                    return Err(Error::NotFits(
+                        "It must contain card SIMPLE with value true".into(),
+                    ));
+                }
+                Some(true) => None,
+            }
+        } else {
+            Some(
+                self.extension()
+                    .ok_or_else(|| missing_keyword(hdu, card_keys::XTENSION))?,
+            )
+        };
+
+        let first = if extension.is_some() {
+            card_keys::XTENSION
+        } else {
+            card_keys::SIMPLE
+        };
+        let mut mandatory = vec![String::from(first), card_keys::BITPIX.into()];
+        if !self.cards().any(|card| matches!(card, Card::Bitpix { .. })) {
+            return Err(missing_keyword(hdu, card_keys::BITPIX));
+        }
+        mandatory.push(card_keys::NAXIS.into());
+        let naxis = self
+            .cards()
+            .find_map(|card| match card {
+                Card::NAxis { value, .. } => Some(*value),
+                _ => None,
+            })
+            .ok_or_else(|| missing_keyword(hdu, card_keys::NAXIS))?;
+        if !(0..=999).contains(&naxis) {
+            return Err(Error::InvalidValue {
+                keyword: card_keys::NAXIS.into(),
+                message: format!("must be between 0 and 999, was {}", naxis),
+            });
+        }
+        for axis in 0..naxis as usize {
+            match self.naxis_n(axis) {
+                Some(length) if length >= 0 => {}
+                Some(length) => {
+                    return Err(Error::InvalidValue {
+                        keyword: format!("{}{}", card_keys::PREFIX_NAXIS_N, axis + 1),
+                        message: format!("must not be negative, was {}", length),
+                    });
+                }
+                None => {
+                    return Err(missing_keyword(
+                        hdu,
+                        &format!("{}{}", card_keys::PREFIX_NAXIS_N, axis + 1),
+                    ));
+                }
+            }
+            mandatory.push(format!("{}{}", card_keys::PREFIX_NAXIS_N, axis + 1));
+        }
+        for (key, value) in [
+            (card_keys::PCOUNT, self.pcount()),
+            (card_keys::GCOUNT, self.group_count()),
+        ] {
+            if let Some(value) = value
+                && value < 0
+            {
+                return Err(Error::InvalidValue {
+                    keyword: key.into(),
+                    message: format!("must not be negative, was {}", value),
+                });
+            }
         }
 
-        let card = Card::try_from(&buf)?;
+        if extension.is_some() {
+            mandatory.push(card_keys::PCOUNT.into());
+            mandatory.push(card_keys::GCOUNT.into());
+        }
+        if matches!(
+            extension,
+            Some(ExtensionType::BinTable | ExtensionType::AsciiTable)
+        ) {
+            mandatory.push(card_keys::TFIELDS.into());
+        }
+
+        // The mandatory keywords have to come first, in this order
+        for (index, key) in mandatory.iter().enumerate() {
+            let problem = match self.record_index(|card| card.key() == *key) {
+                Some(position) if position == index => continue,
+                Some(position) => ParseWarning {
+                    hdu,
+                    card: Some(position),
+                    keyword: key.clone(),
+                    problem: format!("Keyword must be card {} of the header", index),
+                },
+                None => ParseWarning {
+                    hdu,
+                    card: None,
+                    keyword: key.clone(),
+                    problem: "Mandatory keyword is missing".into(),
+                },
+            };
+            options.report(warnings, problem)?;
+        }
+
+        let mut problems: Vec<(String, String)> = vec![];
+        let bitpix = self.bitpix();
+        match extension {
+            None => {
+                if self.groups() == Some(true) && self.naxis_n(0) != Some(0) {
+                    problems.push(("NAXIS1".into(), "Must be 0 for random groups".into()));
+                }
+            }
+            Some(ExtensionType::Image) => {
+                if self.pcount().is_some_and(|pcount| pcount != 0) {
+                    problems.push((
+                        card_keys::PCOUNT.into(),
+                        "Must be 0 for IMAGE extensions".into(),
+                    ));
+                }
+                if self.group_count().is_some_and(|gcount| gcount != 1) {
+                    problems.push((
+                        card_keys::GCOUNT.into(),
+                        "Must be 1 for IMAGE extensions".into(),
+                    ));
+                }
+            }
+            Some(table @ (ExtensionType::BinTable | ExtensionType::AsciiTable)) => {
+                if bitpix != Bitpix::U8 {
+                    problems.push((card_keys::BITPIX.into(), "Must be 8 for tables".into()));
+                }
+                if naxis != 2 {
+                    problems.push((card_keys::NAXIS.into(), "Must be 2 for tables".into()));
+                }
+                if table == ExtensionType::AsciiTable
+                    && self.pcount().is_some_and(|pcount| pcount != 0)
+                {
+                    problems.push((
+                        card_keys::PCOUNT.into(),
+                        "Must be 0 for TABLE extensions".into(),
+                    ));
+                }
+                if self.group_count().is_some_and(|gcount| gcount != 1) {
+                    problems.push((card_keys::GCOUNT.into(), "Must be 1 for tables".into()));
+                }
+
+                let fields = self.table_fields().unwrap_or(0);
+                if !(0..=999).contains(&fields) {
+                    problems.push((
+                        card_keys::TFIELDS.into(),
+                        "Must be between 0 and 999".into(),
+                    ));
+                }
+                for field in 0..fields.clamp(0, 999) as usize {
+                    if !self.cards().any(
+                        |card| matches!(card, Card::TableFormatN { index, .. } if *index == field),
+                    ) {
+                        problems.push((
+                            format!("{}{}", card_keys::PREFIX_TFORM_N, field + 1),
+                            "Mandatory keyword is missing".into(),
+                        ));
+                    }
+                    if table == ExtensionType::AsciiTable && self.table_column(field).is_none() {
+                        problems.push((
+                            format!("{}{}", card_keys::PREFIX_TBCOL_N, field + 1),
+                            "Mandatory keyword is missing".into(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (keyword, problem) in problems {
+            let warning = ParseWarning {
+                hdu,
+                card: self.cards().position(|card| card.key() == keyword),
+                keyword,
+                problem,
+            };
+            options.report(warnings, warning)?;
+        }
 
-        Ok(Some(card))
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    /// Reads whole FITS blocks until the block holding the END record
+    fn read_all_records(reader: &mut Box<dyn ReadSeek>) -> Result<Vec<u8>> {
+        let mut block = [0_u8; BLOCK_NUM_BYTES];
+        let mut raw = vec![];
+
+        loop {
+            match reader.read_exact(&mut block) {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err.into()),
+            }
+            raw.extend_from_slice(&block);
+
+            let records = block.as_chunks::<CARD_NUM_BYTES>().0;
+            if records
+                .iter()
+                .any(|record| record[..8].trim_ascii() == card_keys::END.as_bytes())
+            {
+                break;
+            }
+        }
+        Ok(raw)
     }
 }
 
@@ -944,7 +2220,7 @@ impl fmt::Debug for Header {
             f,
             "Flexible Image Transport System (FITS) Data Unit Header:"
         )?;
-        for card in &self.cards {
+        for card in self.cards() {
             if card != &Card::End {
                 let value = Value::from(card);
                 writeln!(
@@ -962,3 +2238,150 @@ impl fmt::Debug for Header {
         Ok(())
     }
 }
+
+fn missing_keyword(hdu: usize, keyword: &str) -> Error {
+    Error::MissingKeyword {
+        hdu,
+        keyword: keyword.into(),
+    }
+}
+
+/// Splits text into lines of at most `width` characters, breaking at spaces where possible and
+/// at line breaks in the text
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word = word;
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() <= width {
+                line.push(' ');
+                line.push_str(word);
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(core::mem::take(&mut line));
+            }
+            // Words longer than a whole line are split wherever the line is full
+            while word.chars().count() > width {
+                let split = word
+                    .char_indices()
+                    .nth(width)
+                    .map_or(word.len(), |(i, _)| i);
+                lines.push(word[..split].into());
+                word = &word[split..];
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Matches a keyword against a pattern of [`Header::find`], ignoring case
+pub(crate) fn matches_pattern(pattern: &[u8], key: &[u8]) -> bool {
+    match (pattern.split_first(), key.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => (0..=key.len()).any(|skip| matches_pattern(rest, &key[skip..])),
+        (Some((b'#', rest)), Some((first, _))) if first.is_ascii_digit() => {
+            let digits = key.iter().take_while(|b| b.is_ascii_digit()).count();
+            (1..=digits).any(|skip| matches_pattern(rest, &key[skip..]))
+        }
+        (Some((b'?', rest)), Some((_, key_rest))) => matches_pattern(rest, key_rest),
+        (Some((expected, rest)), Some((actual, key_rest))) => {
+            expected.eq_ignore_ascii_case(actual) && matches_pattern(rest, key_rest)
+        }
+        _ => false,
+    }
+}
+
+fn is_structural_key(key: &str) -> bool {
+    const INDEXED: [&str; 3] = [
+        card_keys::PREFIX_NAXIS_N,
+        card_keys::PREFIX_TFORM_N,
+        card_keys::PREFIX_TBCOL_N,
+    ];
+
+    matches!(
+        key,
+        card_keys::SIMPLE
+            | card_keys::XTENSION
+            | card_keys::BITPIX
+            | card_keys::NAXIS
+            | card_keys::PCOUNT
+            | card_keys::GCOUNT
+            | card_keys::TFIELDS
+            | card_keys::THEAP
+            | card_keys::GROUPS
+    ) || INDEXED.iter().any(|prefix| {
+        key.strip_prefix(prefix)
+            .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+    })
+}
+
+/// Joins string values continued with an ampersand over CONTINUE cards into a single card, cf.
+/// FITSv4, section 4.2.1.2
+fn join_continuations(cards: Vec<HeaderCard>) -> Vec<HeaderCard> {
+    let mut joined: Vec<HeaderCard> = Vec::with_capacity(cards.len());
+    let mut continued: Option<(String, Value, Vec<[u8; CARD_NUM_BYTES]>)> = None;
+
+    for entry in cards {
+        if let Card::Continuation {
+            string: Some(string),
+            comment,
+        } = &entry.card
+            && let Some((
+                _,
+                Value::String {
+                    value,
+                    comment: value_comment,
+                },
+                records,
+            )) = &mut continued
+        {
+            value.pop();
+            value.push_str(string);
+            if let Some(comment) = comment {
+                match value_comment {
+                    Some(value_comment) => {
+                        value_comment.push(' ');
+                        value_comment.push_str(comment);
+                    }
+                    None => *value_comment = Some(comment.clone()),
+                }
+            }
+            records.extend(entry.records);
+            if !value.ends_with('&') {
+                joined.push(continued_card(continued.take()));
+            }
+            continue;
+        }
+
+        if continued.is_some() {
+            joined.push(continued_card(continued.take()));
+        }
+        match Value::from(&entry.card) {
+            Value::String { value, comment } if value.ends_with('&') => {
+                continued = Some((
+                    entry.card.key(),
+                    Value::String { value, comment },
+                    entry.records,
+                ));
+            }
+            _ => joined.push(entry),
+        }
+    }
+    if continued.is_some() {
+        joined.push(continued_card(continued.take()));
+    }
+    joined
+}
+
+fn continued_card(continued: Option<(String, Value, Vec<[u8; CARD_NUM_BYTES]>)>) -> HeaderCard {
+    let (key, value, records) = continued.expect("a continued card");
+    let card = Card::new(&key, value.clone()).unwrap_or(Card::Value { name: key, value });
+    HeaderCard::read(card, records)
+}
@@ -0,0 +1,126 @@
+use crate::header::header::matches_pattern;
+use crate::header::{Card, Header, Value};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Options for [`Header::diff_with`]
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    ignore: Vec<String>,
+    tolerance: f64,
+}
+
+impl DiffOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leaves out keywords matching any of the given patterns, see [`Header::find`] for the syntax
+    pub fn ignore(&mut self, keys: &[&str]) -> &mut Self {
+        self.ignore
+            .extend(keys.iter().map(|key| String::from(*key)));
+        self
+    }
+
+    /// Treats numeric values as equal when they differ by at most `tolerance`
+    pub fn tolerance(&mut self, tolerance: f64) -> &mut Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    fn is_ignored(&self, key: &str) -> bool {
+        self.ignore
+            .iter()
+            .any(|pattern| matches_pattern(pattern.as_bytes(), key.as_bytes()))
+    }
+}
+
+/// A difference between two headers, found by [`Header::diff`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeywordDiff {
+    /// The card is only in the other header
+    Added(Card),
+    /// The card is only in this header
+    Removed(Card),
+    /// The value or comment of the card differs
+    Changed { old: Card, new: Card },
+}
+
+impl KeywordDiff {
+    pub fn key(&self) -> String {
+        match self {
+            KeywordDiff::Added(card) | KeywordDiff::Removed(card) => card.key(),
+            KeywordDiff::Changed { old, .. } => old.key(),
+        }
+    }
+}
+
+/// Compares the cards of two headers keyword by keyword. Keywords that appear several times, like
+/// HISTORY, are compared in the order they appear.
+pub(crate) fn diff(old: &Header, new: &Header, options: &DiffOptions) -> Vec<KeywordDiff> {
+    let mut keys: Vec<String> = vec![];
+    for card in old.iter().chain(new.iter()) {
+        let key = card.key();
+        if !options.is_ignored(&key) && !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    let mut differences = vec![];
+    for key in keys {
+        let mut old_cards = old.iter().filter(|card| card.key() == key);
+        let mut new_cards = new.iter().filter(|card| card.key() == key);
+        loop {
+            match (old_cards.next(), new_cards.next()) {
+                (Some(old), Some(new)) => {
+                    if !cards_equal(old, new, options.tolerance) {
+                        differences.push(KeywordDiff::Changed {
+                            old: old.clone(),
+                            new: new.clone(),
+                        });
+                    }
+                }
+                (Some(old), None) => differences.push(KeywordDiff::Removed(old.clone())),
+                (None, Some(new)) => differences.push(KeywordDiff::Added(new.clone())),
+                (None, None) => break,
+            }
+        }
+    }
+    differences
+}
+
+fn cards_equal(old: &Card, new: &Card, tolerance: f64) -> bool {
+    let (old_value, new_value) = (old.value(), new.value());
+    if old_value == Value::Undefined && new_value == Value::Undefined {
+        // Commentary and unparsed cards only have their text
+        return old == new;
+    }
+
+    old.comment() == new.comment() && values_equal(&old_value, &new_value, tolerance)
+}
+
+fn values_equal(old: &Value, new: &Value, tolerance: f64) -> bool {
+    match (as_complex(old), as_complex(new)) {
+        (Some((old_real, old_imaginary)), Some((new_real, new_imaginary))) => {
+            (old_real - new_real).abs() <= tolerance
+                && (old_imaginary - new_imaginary).abs() <= tolerance
+        }
+        _ => old.clone().with_comment(None) == new.clone().with_comment(None),
+    }
+}
+
+/// Numeric values as a complex number, so integers, floats and complex values can be compared
+fn as_complex(value: &Value) -> Option<(f64, f64)> {
+    match value {
+        Value::Integer { value, .. } => Some((*value as f64, 0.0)),
+        Value::Float { value, .. } => Some((*value, 0.0)),
+        Value::ComplexInteger {
+            real, imaginary, ..
+        } => Some((*real as f64, *imaginary as f64)),
+        Value::ComplexFloat {
+            real, imaginary, ..
+        } => Some((*real, *imaginary)),
+        _ => None,
+    }
+}
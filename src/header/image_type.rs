@@ -1,6 +1,6 @@
 use alloc::string::String;
+use alloc::string::ToString;
 use core::fmt::{Display, Formatter};
-use std::prelude::rust_2015::ToString;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ImageType {
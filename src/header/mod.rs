@@ -2,16 +2,31 @@
 mod bayer_pattern;
 mod bitpix;
 mod card;
-mod card_keys;
+pub(crate) mod card_keys;
 mod extension_type;
+#[cfg(feature = "serde")]
+mod from_header;
+mod from_header_value;
 mod header;
+mod header_diff;
 mod image_type;
 mod table_column_format;
+#[cfg(feature = "serde")]
+mod to_cards;
 mod value;
 
 pub use self::bayer_pattern::BayerPattern;
 pub use self::bitpix::Bitpix;
+pub use self::card::Card;
 pub use self::extension_type::ExtensionType;
+pub use self::from_header_value::FromHeaderValue;
 pub use self::header::Header;
+pub use self::header_diff::{DiffOptions, KeywordDiff};
 pub use self::image_type::ImageType;
 pub use self::table_column_format::TableColumnFormat;
+pub use self::value::Value;
+
+#[cfg(feature = "serde")]
+pub use self::from_header::from_header;
+#[cfg(feature = "serde")]
+pub use self::to_cards::to_cards;
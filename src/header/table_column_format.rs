@@ -1,8 +1,6 @@
 use crate::bin_table::Value;
-use alloc::boxed::Box;
 use alloc::format;
 use alloc::string::{String, ToString};
-use core::error::Error;
 use core::str::from_utf8;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -195,7 +193,7 @@ impl From<TableColumnFormat> for String {
 }
 
 impl TryFrom<String> for TableColumnFormat {
-    type Error = Box<dyn Error + Send + Sync>;
+    type Error = crate::Error;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
         let (repeat, format, items) = extract_parts(&value)?;
@@ -220,7 +218,7 @@ impl TryFrom<String> for TableColumnFormat {
             'D' => Ok(TableColumnFormat::F64(repeat)),
             'C' => Ok(TableColumnFormat::C32(repeat)),
             'M' => Ok(TableColumnFormat::M64(repeat)),
-            _ => Err(From::from(format!(
+            _ => Err(crate::Error::TableFormat(format!(
                 "Invalid TableColumnFormat value: {}",
                 value
             ))),
@@ -228,7 +226,7 @@ impl TryFrom<String> for TableColumnFormat {
     }
 }
 
-fn extract_parts(value: &str) -> Result<(usize, char, usize), Box<dyn Error + Send + Sync>> {
+fn extract_parts(value: &str) -> crate::Result<(usize, char, usize)> {
     let mut chars = value.chars().peekable();
     let mut repeat_str = String::new();
     while let Some(c) = chars.peek() {
@@ -245,13 +243,13 @@ fn extract_parts(value: &str) -> Result<(usize, char, usize), Box<dyn Error + Se
     } else {
         repeat_str
             .parse::<usize>()
-            .map_err(|_| "Invalid repeat count")?
+            .map_err(|_| crate::Error::TableFormat(format!("Invalid repeat count: {}", value)))?
     };
 
     // Parse type code
     let code = chars
         .next()
-        .ok_or_else(|| "Missing format code".to_string())?;
+        .ok_or_else(|| crate::Error::TableFormat(format!("Missing format code: {}", value)))?;
 
     let mut width_str = String::new();
     while let Some(c) = chars.peek() {
@@ -268,7 +266,7 @@ fn extract_parts(value: &str) -> Result<(usize, char, usize), Box<dyn Error + Se
     } else {
         width_str
             .parse::<usize>()
-            .map_err(|_| "Invalid string width")?
+            .map_err(|_| crate::Error::TableFormat(format!("Invalid string width: {}", value)))?
     };
 
     Ok((repeat, code, width))
@@ -0,0 +1,409 @@
+use crate::error::Error;
+use crate::header::{Card, Value};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+
+/// Serializes the fields of a struct or the entries of a map into header cards, using the field
+/// names as keywords. Fields that are `None` are left out, and unit values become cards without a
+/// value.
+pub fn to_cards<T: Serialize>(value: &T) -> crate::Result<Vec<Card>> {
+    let mut serializer = Serializer {
+        cards: vec![],
+        key: None,
+    };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.cards)
+}
+
+struct Serializer {
+    cards: Vec<Card>,
+    // The key of the map entry being serialized
+    key: Option<String>,
+}
+
+impl Serializer {
+    fn push<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> crate::Result<()> {
+        let key = key.to_ascii_uppercase();
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.cards.push(Card::new(&key, value)?);
+        }
+        Ok(())
+    }
+}
+
+fn only_structs() -> Error {
+    Error::SerializationError("headers can only be serialized from structs or maps".into())
+}
+
+impl ser::Serializer for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, _v: bool) -> crate::Result<()> {
+        Err(only_structs())
+    }
+
+    fn serialize_i64(self, _v: i64) -> crate::Result<()> {
+        Err(only_structs())
+    }
+
+    fn serialize_u64(self, _v: u64) -> crate::Result<()> {
+        Err(only_structs())
+    }
+
+    fn serialize_f64(self, _v: f64) -> crate::Result<()> {
+        Err(only_structs())
+    }
+
+    fn serialize_i8(self, v: i8) -> crate::Result<()> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> crate::Result<()> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> crate::Result<()> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u8(self, v: u8) -> crate::Result<()> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> crate::Result<()> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> crate::Result<()> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_f32(self, v: f32) -> crate::Result<()> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_char(self, _v: char) -> crate::Result<()> {
+        Err(only_structs())
+    }
+
+    fn serialize_str(self, _v: &str) -> crate::Result<()> {
+        Err(only_structs())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> crate::Result<()> {
+        Err(only_structs())
+    }
+
+    fn serialize_none(self) -> crate::Result<()> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> crate::Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> crate::Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> crate::Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> crate::Result<()> {
+        Err(only_structs())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> crate::Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> crate::Result<()> {
+        Err(only_structs())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> crate::Result<Self::SerializeSeq> {
+        Err(only_structs())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> crate::Result<Self::SerializeTuple> {
+        Err(only_structs())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self::SerializeTupleStruct> {
+        Err(only_structs())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self::SerializeTupleVariant> {
+        Err(only_structs())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> crate::Result<Self::SerializeMap> {
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self::SerializeStruct> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self::SerializeStructVariant> {
+        Err(only_structs())
+    }
+}
+
+impl ser::SerializeStruct for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> crate::Result<()> {
+        self.push(key, value)
+    }
+
+    fn end(self) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> crate::Result<()> {
+        match key.serialize(ValueSerializer)? {
+            Some(Value::String { value, .. }) => {
+                self.key = Some(value);
+                Ok(())
+            }
+            _ => Err(Error::SerializationError("keywords must be strings".into())),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> crate::Result<()> {
+        let key = self.key.take().expect("a key before its value");
+        self.push(&key, value)
+    }
+
+    fn end(self) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serializes a single value, `None` when the card should be left out
+struct ValueSerializer;
+
+fn unsupported(kind: &str) -> Error {
+    Error::SerializationError(format!("{} can not be stored in a header card", kind))
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<Option<Value>, Error>;
+    type SerializeTuple = Impossible<Option<Value>, Error>;
+    type SerializeTupleStruct = Impossible<Option<Value>, Error>;
+    type SerializeTupleVariant = Impossible<Option<Value>, Error>;
+    type SerializeMap = Impossible<Option<Value>, Error>;
+    type SerializeStruct = Impossible<Option<Value>, Error>;
+    type SerializeStructVariant = Impossible<Option<Value>, Error>;
+
+    fn serialize_bool(self, v: bool) -> crate::Result<Option<Value>> {
+        Ok(Some(v.into()))
+    }
+
+    fn serialize_i8(self, v: i8) -> crate::Result<Option<Value>> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> crate::Result<Option<Value>> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> crate::Result<Option<Value>> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> crate::Result<Option<Value>> {
+        Ok(Some(v.into()))
+    }
+
+    fn serialize_u8(self, v: u8) -> crate::Result<Option<Value>> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u16(self, v: u16) -> crate::Result<Option<Value>> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u32(self, v: u32) -> crate::Result<Option<Value>> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u64(self, v: u64) -> crate::Result<Option<Value>> {
+        let v = i64::try_from(v).map_err(|_| unsupported("an integer above i64::MAX"))?;
+        self.serialize_i64(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> crate::Result<Option<Value>> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> crate::Result<Option<Value>> {
+        Ok(Some(v.into()))
+    }
+
+    fn serialize_char(self, v: char) -> crate::Result<Option<Value>> {
+        self.serialize_str(&v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> crate::Result<Option<Value>> {
+        Ok(Some(v.into()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> crate::Result<Option<Value>> {
+        Err(unsupported("a byte array"))
+    }
+
+    fn serialize_none(self) -> crate::Result<Option<Value>> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> crate::Result<Option<Value>> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> crate::Result<Option<Value>> {
+        Ok(Some(Value::Undefined))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> crate::Result<Option<Value>> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> crate::Result<Option<Value>> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> crate::Result<Option<Value>> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> crate::Result<Option<Value>> {
+        Err(unsupported("an enum variant with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> crate::Result<Self::SerializeSeq> {
+        Err(unsupported("a sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> crate::Result<Self::SerializeTuple> {
+        Err(unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self::SerializeTupleStruct> {
+        Err(unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self::SerializeTupleVariant> {
+        Err(unsupported("an enum variant with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> crate::Result<Self::SerializeMap> {
+        Err(unsupported("a map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self::SerializeStruct> {
+        Err(unsupported("a struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self::SerializeStructVariant> {
+        Err(unsupported("an enum variant with data"))
+    }
+}
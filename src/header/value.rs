@@ -1,7 +1,7 @@
 use crate::header::card::Card;
+use alloc::format;
 use alloc::string::String;
-use std::format;
-use std::prelude::rust_2015::ToString;
+use alloc::string::ToString;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
@@ -21,6 +21,16 @@ pub enum Value {
         value: String,
         comment: Option<String>,
     },
+    ComplexInteger {
+        real: i64,
+        imaginary: i64,
+        comment: Option<String>,
+    },
+    ComplexFloat {
+        real: f64,
+        imaginary: f64,
+        comment: Option<String>,
+    },
     Undefined,
     Invalid(String),
 }
@@ -32,6 +42,8 @@ impl Value {
             Value::Float { comment, .. } => comment,
             Value::Logical { comment, .. } => comment,
             Value::String { comment, .. } => comment,
+            Value::ComplexInteger { comment, .. } => comment,
+            Value::ComplexFloat { comment, .. } => comment,
             Value::Undefined => &None,
             Value::Invalid(_) => &None,
         };
@@ -56,10 +68,91 @@ impl Value {
                 false => "F".to_string(),
             },
             Value::String { value, .. } => value.to_string(),
+            Value::ComplexInteger {
+                real, imaginary, ..
+            } => {
+                format!("({}, {})", real, imaginary)
+            }
+            Value::ComplexFloat {
+                real, imaginary, ..
+            } => {
+                format!("({}, {})", real, imaginary)
+            }
             Value::Undefined => "".to_string(),
             Value::Invalid(_) => "".to_string(),
         }
     }
+
+    /// Replaces the comment of this value, undefined and invalid values can not hold a comment
+    pub fn with_comment(self, comment: Option<String>) -> Self {
+        match self {
+            Value::Integer { value, .. } => Value::Integer { value, comment },
+            Value::Float { value, .. } => Value::Float { value, comment },
+            Value::Logical { value, .. } => Value::Logical { value, comment },
+            Value::String { value, .. } => Value::String { value, comment },
+            Value::ComplexInteger {
+                real, imaginary, ..
+            } => Value::ComplexInteger {
+                real,
+                imaginary,
+                comment,
+            },
+            Value::ComplexFloat {
+                real, imaginary, ..
+            } => Value::ComplexFloat {
+                real,
+                imaginary,
+                comment,
+            },
+            Value::Undefined => Value::Undefined,
+            Value::Invalid(value) => Value::Invalid(value),
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer {
+            value,
+            comment: None,
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float {
+            value,
+            comment: None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Logical {
+            value,
+            comment: None,
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String {
+            value: value.to_string(),
+            comment: None,
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String {
+            value,
+            comment: None,
+        }
+    }
 }
 
 impl From<&Card> for Value {
@@ -150,7 +243,7 @@ impl From<Card> for Value {
             },
             Card::Value { value, .. } => value,
             Card::Continuation { .. } => Value::Undefined,
-            Card::Hierarch { .. } => Value::Undefined,
+            Card::Hierarch { value, .. } => value.clone(),
             Card::Space => Value::Undefined,
             Card::Undefined(_) => Value::Undefined,
             Card::TableFormatN { value, comment, .. } => Value::String {
@@ -0,0 +1,481 @@
+//! Algorithms that turn a bayer mosaic into a full resolution color image
+
+use crate::Result;
+use crate::error::Error;
+use crate::header::BayerPattern;
+use alloc::vec;
+use alloc::vec::Vec;
+use image::{ImageBuffer, Luma, Pixel, Primitive, Rgb};
+
+const RED: usize = 0;
+const GREEN: usize = 1;
+const BLUE: usize = 2;
+
+/// The neighbours of a pixel, starting north and going clockwise
+const DIRECTIONS: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// The algorithm used to interpolate the colors missing at every pixel of a bayer mosaic
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DemosaicAlgorithm {
+    /// Every 2x2 cell becomes a single color, which is fast but halves the detail
+    Superpixel,
+
+    /// Averages the nearest pixels of every missing color
+    #[default]
+    Bilinear,
+
+    /// Variable number of gradients, averages color differences along the smoothest directions
+    Vng,
+
+    /// Adaptive homogeneity-directed, picks horizontal or vertical interpolation per pixel
+    Ahd,
+}
+
+/// Demosaics an image with the given algorithm, the result has the size of the mosaic. Mosaics
+/// smaller than 2x2 pixels have no complete filter cell and are rejected.
+pub fn demosaic<T: Primitive>(
+    mosaic: &ImageBuffer<Luma<T>, Vec<T>>,
+    bayer_pattern: BayerPattern,
+    algorithm: DemosaicAlgorithm,
+) -> Result<ImageBuffer<Rgb<T>, Vec<T>>>
+where
+    Rgb<T>: Pixel<Subpixel = T>,
+{
+    match algorithm {
+        DemosaicAlgorithm::Superpixel => superpixel(mosaic, bayer_pattern),
+        DemosaicAlgorithm::Bilinear => bilinear(mosaic, bayer_pattern),
+        DemosaicAlgorithm::Vng => vng(mosaic, bayer_pattern),
+        DemosaicAlgorithm::Ahd => ahd(mosaic, bayer_pattern),
+    }
+}
+
+/// Gives every pixel the color of its 2x2 cell
+pub fn superpixel<T: Primitive>(
+    mosaic: &ImageBuffer<Luma<T>, Vec<T>>,
+    bayer_pattern: BayerPattern,
+) -> Result<ImageBuffer<Rgb<T>, Vec<T>>>
+where
+    Rgb<T>: Pixel<Subpixel = T>,
+{
+    let mosaic = Mosaic::new(mosaic, bayer_pattern)?;
+    Ok(mosaic.to_buffer(|x, y| mosaic.cell_color(x - x % 2, y - y % 2)))
+}
+
+/// Turns every 2x2 cell into a single pixel, the result has half the width and height of the
+/// mosaic
+pub fn binned_superpixel<T: Primitive>(
+    mosaic: &ImageBuffer<Luma<T>, Vec<T>>,
+    bayer_pattern: BayerPattern,
+) -> Result<ImageBuffer<Rgb<T>, Vec<T>>>
+where
+    Rgb<T>: Pixel<Subpixel = T>,
+{
+    let mosaic = Mosaic::new(mosaic, bayer_pattern)?;
+    let (width, height) = (mosaic.width / 2, mosaic.height / 2);
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            pixels.push(mosaic.cell_color(x * 2, y * 2));
+        }
+    }
+    Ok(rgb_buffer(width, height, pixels))
+}
+
+/// Averages the pixels of every missing color in the surrounding 3x3 block
+pub fn bilinear<T: Primitive>(
+    mosaic: &ImageBuffer<Luma<T>, Vec<T>>,
+    bayer_pattern: BayerPattern,
+) -> Result<ImageBuffer<Rgb<T>, Vec<T>>>
+where
+    Rgb<T>: Pixel<Subpixel = T>,
+{
+    let mosaic = Mosaic::new(mosaic, bayer_pattern)?;
+    Ok(mosaic.to_buffer(|x, y| {
+        [RED, GREEN, BLUE].map(|color| mosaic.interpolate(&mosaic.values, x, y, color))
+    }))
+}
+
+/// Variable number of gradients, cf. Chang, Cheung and Pang, "Color filter array recovery using
+/// a threshold-based variable number of gradients", 1999
+pub fn vng<T: Primitive>(
+    mosaic: &ImageBuffer<Luma<T>, Vec<T>>,
+    bayer_pattern: BayerPattern,
+) -> Result<ImageBuffer<Rgb<T>, Vec<T>>>
+where
+    Rgb<T>: Pixel<Subpixel = T>,
+{
+    let mosaic = Mosaic::new(mosaic, bayer_pattern)?;
+    Ok(mosaic.to_buffer(|x, y| mosaic.vng_color(x, y)))
+}
+
+/// Adaptive homogeneity-directed demosaicing, cf. Hirakawa and Parks, "Adaptive
+/// homogeneity-directed demosaicing algorithm", 2005
+pub fn ahd<T: Primitive>(
+    mosaic: &ImageBuffer<Luma<T>, Vec<T>>,
+    bayer_pattern: BayerPattern,
+) -> Result<ImageBuffer<Rgb<T>, Vec<T>>>
+where
+    Rgb<T>: Pixel<Subpixel = T>,
+{
+    let mosaic = Mosaic::new(mosaic, bayer_pattern)?;
+    let horizontal = mosaic.colors_from_green(&mosaic.directional_green((1, 0)));
+    let vertical = mosaic.colors_from_green(&mosaic.directional_green((0, 1)));
+    let horizontal_lab = horizontal.iter().map(lab).collect::<Vec<_>>();
+    let vertical_lab = vertical.iter().map(lab).collect::<Vec<_>>();
+
+    // How many neighbours of every pixel have a similar color, cf. section III.B
+    let mut horizontal_homogeneity = vec![0_u32; horizontal.len()];
+    let mut vertical_homogeneity = vec![0_u32; vertical.len()];
+    for y in 0..mosaic.height {
+        for x in 0..mosaic.width {
+            let distances = |lab: &[[f64; 3]], neighbours: [(isize, isize); 2]| {
+                neighbours.map(|(dx, dy)| {
+                    lab_distance(&lab[mosaic.index(x, y)], &mosaic.lab_at(lab, x, y, dx, dy))
+                })
+            };
+            let [h1, h2] = distances(&horizontal_lab, [(-1, 0), (1, 0)]);
+            let [v1, v2] = distances(&vertical_lab, [(0, -1), (0, 1)]);
+            let luminance = h1.0.max(h2.0).min(v1.0.max(v2.0));
+            let chrominance = h1.1.max(h2.1).min(v1.1.max(v2.1));
+
+            let index = mosaic.index(x, y);
+            for (lab, homogeneity) in [
+                (&horizontal_lab, &mut horizontal_homogeneity),
+                (&vertical_lab, &mut vertical_homogeneity),
+            ] {
+                homogeneity[index] = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                    .iter()
+                    .filter(|(dx, dy)| {
+                        let (l, c) = lab_distance(&lab[index], &mosaic.lab_at(lab, x, y, *dx, *dy));
+                        l <= luminance && c <= chrominance
+                    })
+                    .count() as u32;
+            }
+        }
+    }
+
+    Ok(mosaic.to_buffer(|x, y| {
+        let score = |homogeneity: &[u32]| {
+            let mut score = 0;
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    score += homogeneity[mosaic.reflected_index(x, y, dx, dy)];
+                }
+            }
+            score
+        };
+        let index = mosaic.index(x, y);
+        let (h, v) = (score(&horizontal_homogeneity), score(&vertical_homogeneity));
+        if h > v {
+            horizontal[index]
+        } else if v > h {
+            vertical[index]
+        } else {
+            [RED, GREEN, BLUE]
+                .map(|color| (horizontal[index][color] + vertical[index][color]) / 2.0)
+        }
+    }))
+}
+
+struct Mosaic {
+    values: Vec<f64>,
+    width: usize,
+    height: usize,
+    bayer_pattern: BayerPattern,
+}
+
+impl Mosaic {
+    fn new<T: Primitive>(
+        mosaic: &ImageBuffer<Luma<T>, Vec<T>>,
+        bayer_pattern: BayerPattern,
+    ) -> Result<Self> {
+        // Mirroring at the edges needs a neighbour of the same filter color in both directions
+        if mosaic.width() < 2 || mosaic.height() < 2 {
+            return Err(Error::InvalidImage(
+                "Can not demosaic a mosaic smaller than 2x2 pixels".into(),
+            ));
+        }
+        Ok(Self {
+            values: mosaic
+                .iter()
+                .map(|value| value.to_f64().unwrap_or(f64::NAN))
+                .collect(),
+            width: mosaic.width() as usize,
+            height: mosaic.height() as usize,
+            bayer_pattern,
+        })
+    }
+
+    fn to_buffer<T: Primitive>(
+        &self,
+        color: impl Fn(usize, usize) -> [f64; 3],
+    ) -> ImageBuffer<Rgb<T>, Vec<T>>
+    where
+        Rgb<T>: Pixel<Subpixel = T>,
+    {
+        let mut pixels = Vec::with_capacity(self.width * self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                pixels.push(color(x, y));
+            }
+        }
+        rgb_buffer(self.width, self.height, pixels)
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    /// The index of a pixel relative to (x, y). Positions outside the image are mirrored at the
+    /// edge, which keeps the color of the filter.
+    fn reflected_index(&self, x: usize, y: usize, dx: isize, dy: isize) -> usize {
+        self.index(
+            reflect(x as isize + dx, self.width),
+            reflect(y as isize + dy, self.height),
+        )
+    }
+
+    fn value(&self, x: usize, y: usize, dx: isize, dy: isize) -> f64 {
+        self.values[self.reflected_index(x, y, dx, dy)]
+    }
+
+    fn lab_at(&self, lab: &[[f64; 3]], x: usize, y: usize, dx: isize, dy: isize) -> [f64; 3] {
+        lab[self.reflected_index(x, y, dx, dy)]
+    }
+
+    /// The filter color of a pixel relative to (x, y)
+    fn color(&self, x: usize, y: usize, dx: isize, dy: isize) -> usize {
+        let cell = match self.bayer_pattern {
+            BayerPattern::RGGB => [[RED, GREEN], [GREEN, BLUE]],
+            BayerPattern::BGGR => [[BLUE, GREEN], [GREEN, RED]],
+            BayerPattern::GRBG => [[GREEN, RED], [BLUE, GREEN]],
+            BayerPattern::GBRG => [[GREEN, BLUE], [RED, GREEN]],
+        };
+        let column = (x as isize + dx).rem_euclid(2) as usize;
+        let row = (y as isize + dy).rem_euclid(2) as usize;
+        cell[row][column]
+    }
+
+    /// The average color of the 2x2 cell starting at (x, y)
+    fn cell_color(&self, x: usize, y: usize) -> [f64; 3] {
+        let mut sums = [0.0; 3];
+        let mut counts = [0.0; 3];
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let color = self.color(x, y, dx, dy);
+            sums[color] += self.value(x, y, dx, dy);
+            counts[color] += 1.0;
+        }
+        [RED, GREEN, BLUE].map(|color| sums[color] / counts[color])
+    }
+
+    /// The value of `color` at (x, y) in `plane`, averaged over the surrounding 3x3 block when
+    /// the pixel has a different filter color
+    fn interpolate(&self, plane: &[f64], x: usize, y: usize, color: usize) -> f64 {
+        if self.color(x, y, 0, 0) == color {
+            return plane[self.index(x, y)];
+        }
+        let mut sum = 0.0;
+        let mut count = 0.0;
+        for (dx, dy) in DIRECTIONS {
+            if self.color(x, y, dx, dy) == color {
+                sum += plane[self.reflected_index(x, y, dx, dy)];
+                count += 1.0;
+            }
+        }
+        sum / count
+    }
+
+    /// The average of every color in the 3x3 block centered at (x + dx, y + dy)
+    fn block_color(&self, x: usize, y: usize, dx: isize, dy: isize) -> [f64; 3] {
+        let mut sums = [0.0; 3];
+        let mut counts = [0.0; 3];
+        for by in dy - 1..=dy + 1 {
+            for bx in dx - 1..=dx + 1 {
+                let color = self.color(x, y, bx, by);
+                sums[color] += self.value(x, y, bx, by);
+                counts[color] += 1.0;
+            }
+        }
+        [RED, GREEN, BLUE].map(|color| sums[color] / counts[color])
+    }
+
+    /// The sum of differences between pixels of the same color along a direction, through and
+    /// beside (x, y). Pairs of differently colored pixels are skipped.
+    fn gradient(&self, x: usize, y: usize, (dx, dy): (isize, isize)) -> f64 {
+        let pairs = if dx == 0 || dy == 0 {
+            let (px, py) = (-dy, dx);
+            [
+                ((dx, dy), (-dx, -dy), 1.0),
+                ((2 * dx, 2 * dy), (0, 0), 1.0),
+                ((dx + px, dy + py), (px - dx, py - dy), 0.5),
+                ((dx - px, dy - py), (-px - dx, -py - dy), 0.5),
+                ((2 * dx + px, 2 * dy + py), (px, py), 0.5),
+                ((2 * dx - px, 2 * dy - py), (-px, -py), 0.5),
+            ]
+        } else {
+            [
+                ((dx, dy), (-dx, -dy), 1.0),
+                ((2 * dx, 2 * dy), (0, 0), 1.0),
+                ((0, dy), (-dx, 0), 0.5),
+                ((dx, 0), (0, -dy), 0.5),
+                ((dx, 2 * dy), (0, dy), 0.5),
+                ((2 * dx, dy), (dx, 0), 0.5),
+            ]
+        };
+        pairs
+            .iter()
+            .filter(|(a, b, _)| self.color(x, y, a.0, a.1) == self.color(x, y, b.0, b.1))
+            .map(|(a, b, weight)| {
+                weight * (self.value(x, y, a.0, a.1) - self.value(x, y, b.0, b.1)).abs()
+            })
+            .sum()
+    }
+
+    fn vng_color(&self, x: usize, y: usize) -> [f64; 3] {
+        let gradients = DIRECTIONS.map(|direction| self.gradient(x, y, direction));
+        let min = gradients.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = gradients.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let threshold = 1.5 * min + 0.5 * (max - min);
+
+        let mut sums = [0.0; 3];
+        let mut count = 0.0;
+        for ((dx, dy), gradient) in DIRECTIONS.iter().zip(gradients) {
+            if gradient <= threshold {
+                let block = self.block_color(x, y, *dx, *dy);
+                for color in [RED, GREEN, BLUE] {
+                    sums[color] += block[color];
+                }
+                count += 1.0;
+            }
+        }
+
+        let own = self.color(x, y, 0, 0);
+        let value = self.values[self.index(x, y)];
+        if count == 0.0 {
+            // Only happens for NaN gradients
+            return self.block_color(x, y, 0, 0);
+        }
+        [RED, GREEN, BLUE].map(|color| {
+            if color == own {
+                value
+            } else {
+                value + (sums[color] - sums[own]) / count
+            }
+        })
+    }
+
+    /// Green at every pixel, interpolated along one direction with a correction by the
+    /// curvature of the pixel's own color, cf. section III.A
+    fn directional_green(&self, (dx, dy): (isize, isize)) -> Vec<f64> {
+        let mut green = Vec::with_capacity(self.values.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.color(x, y, 0, 0) == GREEN {
+                    green.push(self.values[self.index(x, y)]);
+                    continue;
+                }
+                let before = self.value(x, y, -dx, -dy);
+                let after = self.value(x, y, dx, dy);
+                let curvature = 2.0 * self.value(x, y, 0, 0)
+                    - self.value(x, y, -2 * dx, -2 * dy)
+                    - self.value(x, y, 2 * dx, 2 * dy);
+                let estimate = (before + after) / 2.0 + curvature / 4.0;
+                green.push(estimate.clamp(before.min(after), before.max(after)));
+            }
+        }
+        green
+    }
+
+    /// Red and blue from the differences to a full green plane
+    fn colors_from_green(&self, green: &[f64]) -> Vec<[f64; 3]> {
+        let differences = self
+            .values
+            .iter()
+            .zip(green)
+            .map(|(value, green)| value - green)
+            .collect::<Vec<_>>();
+        let mut colors = Vec::with_capacity(self.values.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let green = green[self.index(x, y)];
+                colors.push([
+                    green + self.interpolate(&differences, x, y, RED),
+                    green,
+                    green + self.interpolate(&differences, x, y, BLUE),
+                ]);
+            }
+        }
+        colors
+    }
+}
+
+fn reflect(position: isize, len: usize) -> usize {
+    let last = len as isize - 1;
+    let position = if position < 0 {
+        -position
+    } else if position > last {
+        2 * last - position
+    } else {
+        position
+    };
+    position.clamp(0, last.max(0)) as usize
+}
+
+fn rgb_buffer<T: Primitive>(
+    width: usize,
+    height: usize,
+    pixels: Vec<[f64; 3]>,
+) -> ImageBuffer<Rgb<T>, Vec<T>>
+where
+    Rgb<T>: Pixel<Subpixel = T>,
+{
+    let min = T::min_value().to_f64().unwrap_or(f64::MIN);
+    let max = T::max_value().to_f64().unwrap_or(f64::MAX);
+    let data = pixels
+        .into_iter()
+        .flatten()
+        .map(|value| {
+            // Interpolation can overshoot the range of the type, saturate instead of turning black
+            let value = value.clamp(min, max);
+            T::from(value).unwrap_or(if value > 0.0 {
+                T::max_value()
+            } else {
+                T::min_value()
+            })
+        })
+        .collect();
+    ImageBuffer::from_raw(width as u32, height as u32, data).expect("a buffer of the same size")
+}
+
+/// CIELAB of a linear sRGB color, with a D65 white point
+fn lab(rgb: &[f64; 3]) -> [f64; 3] {
+    let [r, g, b] = *rgb;
+    let x = (0.412453 * r + 0.357580 * g + 0.180423 * b) / 0.950456;
+    let y = 0.212671 * r + 0.715160 * g + 0.072169 * b;
+    let z = (0.019334 * r + 0.119193 * g + 0.950227 * b) / 1.088754;
+    let f = |t: f64| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// The luminance and chrominance distance of two CIELAB colors
+fn lab_distance(a: &[f64; 3], b: &[f64; 3]) -> (f64, f64) {
+    let luminance = (a[0] - b[0]).abs();
+    let chrominance = ((a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt();
+    (luminance, chrominance)
+}
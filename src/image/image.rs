@@ -1,18 +1,25 @@
+use crate::Result;
+use crate::error::Error;
 use crate::header::{BayerPattern, Bitpix, Header};
-use crate::image::ImageData;
+#[cfg(feature = "image")]
+use crate::image::DemosaicAlgorithm;
+use crate::image::{ImageData, Normalization};
 use alloc::boxed::Box;
 use alloc::vec::Vec;
-use core::error::Error;
 use image::{ImageBuffer, Luma, Rgb, RgbImage};
-use std::ops::Deref;
 
 #[derive(Debug, Clone)]
 pub enum Image {
     F64(ImageData<f64>),
     F32(ImageData<f32>),
+    I64(ImageData<i64>),
+    U64(ImageData<u64>),
     I32(ImageData<i32>),
+    U32(ImageData<u32>),
     I16(ImageData<i16>),
+    U16(ImageData<u16>),
     U8(ImageData<u8>),
+    I8(ImageData<i8>),
 }
 
 impl Image {
@@ -21,9 +28,14 @@ impl Image {
         match self {
             Self::F64(image) => image.width(),
             Self::F32(image) => image.width(),
+            Self::I64(image) => image.width(),
+            Self::U64(image) => image.width(),
             Self::I32(image) => image.width(),
+            Self::U32(image) => image.width(),
             Self::I16(image) => image.width(),
+            Self::U16(image) => image.width(),
             Self::U8(image) => image.width(),
+            Self::I8(image) => image.width(),
         }
     }
 
@@ -32,9 +44,14 @@ impl Image {
         match self {
             Self::F64(image) => image.height(),
             Self::F32(image) => image.height(),
+            Self::I64(image) => image.height(),
+            Self::U64(image) => image.height(),
             Self::I32(image) => image.height(),
+            Self::U32(image) => image.height(),
             Self::I16(image) => image.height(),
+            Self::U16(image) => image.height(),
             Self::U8(image) => image.height(),
+            Self::I8(image) => image.height(),
         }
     }
 
@@ -43,43 +60,169 @@ impl Image {
         match self {
             Self::F64(image) => image.bayer_pattern(),
             Self::F32(image) => image.bayer_pattern(),
+            Self::I64(image) => image.bayer_pattern(),
+            Self::U64(image) => image.bayer_pattern(),
             Self::I32(image) => image.bayer_pattern(),
+            Self::U32(image) => image.bayer_pattern(),
             Self::I16(image) => image.bayer_pattern(),
+            Self::U16(image) => image.bayer_pattern(),
             Self::U8(image) => image.bayer_pattern(),
+            Self::I8(image) => image.bayer_pattern(),
         }
     }
 
-    /// Returns a normalised version of the image, where all values are converted into f64 in the range of 0.0 - 1.0
+    /// The physical values of all pixels, BZERO + BSCALE * raw value
+    #[cfg(feature = "image")]
+    pub fn physical(&self) -> ImageBuffer<Luma<f64>, Vec<f64>> {
+        match self {
+            Self::F64(image) => image.physical(),
+            Self::F32(image) => image.physical(),
+            Self::I64(image) => image.physical(),
+            Self::U64(image) => image.physical(),
+            Self::I32(image) => image.physical(),
+            Self::U32(image) => image.physical(),
+            Self::I16(image) => image.physical(),
+            Self::U16(image) => image.physical(),
+            Self::U8(image) => image.physical(),
+            Self::I8(image) => image.physical(),
+        }
+    }
+
+    /// The physical value of a pixel, or None if the pixel is null
+    pub fn pixel(&self, x: u32, y: u32) -> Option<f64> {
+        match self {
+            Self::F64(image) => image.pixel(x, y),
+            Self::F32(image) => image.pixel(x, y),
+            Self::I64(image) => image.pixel(x, y),
+            Self::U64(image) => image.pixel(x, y),
+            Self::I32(image) => image.pixel(x, y),
+            Self::U32(image) => image.pixel(x, y),
+            Self::I16(image) => image.pixel(x, y),
+            Self::U16(image) => image.pixel(x, y),
+            Self::U8(image) => image.pixel(x, y),
+            Self::I8(image) => image.pixel(x, y),
+        }
+    }
+
+    /// For every pixel in row order, whether it holds a value. Integer pixels equal to BLANK and
+    /// float pixels that are NaN are null.
+    pub fn mask(&self) -> Vec<bool> {
+        match self {
+            Self::F64(image) => image.mask(),
+            Self::F32(image) => image.mask(),
+            Self::I64(image) => image.mask(),
+            Self::U64(image) => image.mask(),
+            Self::I32(image) => image.mask(),
+            Self::U32(image) => image.mask(),
+            Self::I16(image) => image.mask(),
+            Self::U16(image) => image.mask(),
+            Self::U8(image) => image.mask(),
+            Self::I8(image) => image.mask(),
+        }
+    }
+
+    /// The coordinates and physical values of all pixels that are not null
+    pub fn valid_pixels(&self) -> Box<dyn Iterator<Item = (u32, u32, f64)> + '_> {
+        match self {
+            Self::F64(image) => Box::new(image.valid_pixels()),
+            Self::F32(image) => Box::new(image.valid_pixels()),
+            Self::I64(image) => Box::new(image.valid_pixels()),
+            Self::U64(image) => Box::new(image.valid_pixels()),
+            Self::I32(image) => Box::new(image.valid_pixels()),
+            Self::U32(image) => Box::new(image.valid_pixels()),
+            Self::I16(image) => Box::new(image.valid_pixels()),
+            Self::U16(image) => Box::new(image.valid_pixels()),
+            Self::U8(image) => Box::new(image.valid_pixels()),
+            Self::I8(image) => Box::new(image.valid_pixels()),
+        }
+    }
+
+    /// The smallest and largest finite physical value, or None if there are no finite values
+    pub fn data_range(&self) -> Option<(f64, f64)> {
+        match self {
+            Self::F64(image) => image.data_range(),
+            Self::F32(image) => image.data_range(),
+            Self::I64(image) => image.data_range(),
+            Self::U64(image) => image.data_range(),
+            Self::I32(image) => image.data_range(),
+            Self::U32(image) => image.data_range(),
+            Self::I16(image) => image.data_range(),
+            Self::U16(image) => image.data_range(),
+            Self::U8(image) => image.data_range(),
+            Self::I8(image) => image.data_range(),
+        }
+    }
+
+    /// Returns a normalised version of the image, where the physical values are scaled into the
+    /// range of 0.0 - 1.0 using DATAMIN and DATAMAX, or the data range when they are missing
     #[cfg(feature = "image")]
     pub fn normalized(&self) -> ImageBuffer<Luma<f64>, Vec<f64>> {
+        self.normalized_with(Normalization::default())
+    }
+
+    /// Returns a normalised version of the image, using the given range of physical values
+    #[cfg(feature = "image")]
+    pub fn normalized_with(
+        &self,
+        normalization: Normalization,
+    ) -> ImageBuffer<Luma<f64>, Vec<f64>> {
         match self {
-            Self::F64(image) => image.deref().clone(),
-            Self::F32(image) => image.normalized(),
-            Self::I32(image) => image.normalized(),
-            Self::I16(image) => image.normalized(),
-            Self::U8(image) => image.normalized(),
+            Self::F64(image) => image.normalized_with(normalization),
+            Self::F32(image) => image.normalized_with(normalization),
+            Self::I64(image) => image.normalized_with(normalization),
+            Self::U64(image) => image.normalized_with(normalization),
+            Self::I32(image) => image.normalized_with(normalization),
+            Self::U32(image) => image.normalized_with(normalization),
+            Self::I16(image) => image.normalized_with(normalization),
+            Self::U16(image) => image.normalized_with(normalization),
+            Self::U8(image) => image.normalized_with(normalization),
+            Self::I8(image) => image.normalized_with(normalization),
         }
     }
 
     /// Performs a superpixel demosaic and returns a normalised version. The superpixel algorithm is fast, but essentially cutting the resolution in half.
     #[cfg(feature = "image")]
-    pub fn normalized_superpixel(
-        &self,
-    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>, Box<dyn Error + Send + Sync>> {
+    pub fn normalized_superpixel(&self) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>> {
         match self {
             Self::F64(image) => image.normalized_superpixel(),
             Self::F32(image) => image.normalized_superpixel(),
+            Self::I64(image) => image.normalized_superpixel(),
+            Self::U64(image) => image.normalized_superpixel(),
             Self::I32(image) => image.normalized_superpixel(),
+            Self::U32(image) => image.normalized_superpixel(),
             Self::I16(image) => image.normalized_superpixel(),
+            Self::U16(image) => image.normalized_superpixel(),
             Self::U8(image) => image.normalized_superpixel(),
+            Self::I8(image) => image.normalized_superpixel(),
         }
     }
 
-    /// Converts this image into a RgbImage from image-rs
+    /// Demosaics a normalised version of the image into a color image of the same size
     #[cfg(feature = "image")]
-    pub fn rgb_image(&self) -> Result<RgbImage, Box<dyn Error + Send + Sync>> {
+    pub fn normalized_demosaic(
+        &self,
+        algorithm: DemosaicAlgorithm,
+    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>> {
+        match self {
+            Self::F64(image) => image.normalized_demosaic(algorithm),
+            Self::F32(image) => image.normalized_demosaic(algorithm),
+            Self::I64(image) => image.normalized_demosaic(algorithm),
+            Self::U64(image) => image.normalized_demosaic(algorithm),
+            Self::I32(image) => image.normalized_demosaic(algorithm),
+            Self::U32(image) => image.normalized_demosaic(algorithm),
+            Self::I16(image) => image.normalized_demosaic(algorithm),
+            Self::U16(image) => image.normalized_demosaic(algorithm),
+            Self::U8(image) => image.normalized_demosaic(algorithm),
+            Self::I8(image) => image.normalized_demosaic(algorithm),
+        }
+    }
+
+    /// Converts this image into a RgbImage from image-rs. Images with a bayer pattern are
+    /// demosaiced with the given algorithm, monochrome images become gray.
+    #[cfg(feature = "image")]
+    pub fn rgb_image(&self, algorithm: DemosaicAlgorithm) -> Result<RgbImage> {
         if self.bayer_pattern().is_some() {
-            let normalized = self.normalized_superpixel()?;
+            let normalized = self.normalized_demosaic(algorithm)?;
             let mut buffer = RgbImage::new(normalized.width(), normalized.height());
             for (x, y, pixel) in buffer.enumerate_pixels_mut() {
                 let r_pixel = (u8::MAX as f64 * normalized.get_pixel(x, y)[0]) as u8;
@@ -105,15 +248,74 @@ impl Image {
         }
     }
 
-    pub(crate) fn from_data_and_header(
+    pub(crate) fn from_data_and_header(data: Vec<u8>, header: &Header) -> Result<Self> {
+        let width = header.naxis_n(0).unwrap_or(0) as usize;
+        let height = header.naxis_n(1).unwrap_or(0) as usize;
+        Self::from_region_data(data, width, height, header.bayer_pattern(), header)
+    }
+
+    /// Decodes a part of an image plane, with the value conventions of the header
+    pub(crate) fn from_region_data(
         data: Vec<u8>,
+        width: usize,
+        height: usize,
+        bayer_pattern: Option<BayerPattern>,
         header: &Header,
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
-        let width = header.naxis_n(0).unwrap();
-        let height = header.naxis_n(1).unwrap();
-        let zero_offset = header.bzero().unwrap();
-        let scale = header.bscale().unwrap();
-        let bayer_pattern = header.bayer_pattern();
+    ) -> Result<Self> {
+        let (data_min, data_max) = (header.data_min(), header.data_max());
+        // Images read with an integer offset hold BLANK shifted by that offset
+        let offset = if header.has_integer_offset() {
+            header.bzero().unwrap_or(0.0) as i128
+        } else {
+            0
+        };
+        let blank = header.blank().map(|blank| blank as i128 + offset);
+
+        Ok(
+            match Self::decode(data, width, height, bayer_pattern, header)? {
+                Self::F64(image) => Self::F64(image.with_data_range(data_min, data_max)),
+                Self::F32(image) => Self::F32(image.with_data_range(data_min, data_max)),
+                Self::I64(image) => {
+                    Self::I64(image.with_data_range(data_min, data_max).with_blank(blank))
+                }
+                Self::U64(image) => {
+                    Self::U64(image.with_data_range(data_min, data_max).with_blank(blank))
+                }
+                Self::I32(image) => {
+                    Self::I32(image.with_data_range(data_min, data_max).with_blank(blank))
+                }
+                Self::U32(image) => {
+                    Self::U32(image.with_data_range(data_min, data_max).with_blank(blank))
+                }
+                Self::I16(image) => {
+                    Self::I16(image.with_data_range(data_min, data_max).with_blank(blank))
+                }
+                Self::U16(image) => {
+                    Self::U16(image.with_data_range(data_min, data_max).with_blank(blank))
+                }
+                Self::U8(image) => {
+                    Self::U8(image.with_data_range(data_min, data_max).with_blank(blank))
+                }
+                Self::I8(image) => {
+                    Self::I8(image.with_data_range(data_min, data_max).with_blank(blank))
+                }
+            },
+        )
+    }
+
+    fn decode(
+        data: Vec<u8>,
+        width: usize,
+        height: usize,
+        bayer_pattern: Option<BayerPattern>,
+        header: &Header,
+    ) -> Result<Self> {
+        let zero_offset = header.bzero().unwrap_or(0.0);
+        let scale = header.bscale().unwrap_or(1.0);
+
+        if header.has_integer_offset() {
+            return Self::from_offset_data(data, width, height, bayer_pattern, header);
+        }
 
         match header.bitpix() {
             Bitpix::F64 => {
@@ -124,8 +326,8 @@ impl Image {
                     .map(|i| f64::from_be_bytes(*i))
                     .collect::<Vec<_>>();
                 Ok(Image::F64(ImageData::<f64>::from_data(
-                    width as usize,
-                    height as usize,
+                    width,
+                    height,
                     zero_offset,
                     scale,
                     bayer_pattern,
@@ -140,8 +342,8 @@ impl Image {
                     .map(|i| f32::from_be_bytes(*i))
                     .collect::<Vec<_>>();
                 Ok(Image::F32(ImageData::<f32>::from_data(
-                    width as usize,
-                    height as usize,
+                    width,
+                    height,
                     zero_offset,
                     scale,
                     bayer_pattern,
@@ -149,8 +351,8 @@ impl Image {
                 )?))
             }
             Bitpix::U8 => Ok(Image::U8(ImageData::<u8>::from_data(
-                width as usize,
-                height as usize,
+                width,
+                height,
                 zero_offset,
                 scale,
                 bayer_pattern,
@@ -164,8 +366,24 @@ impl Image {
                     .map(|i| i16::from_be_bytes(*i))
                     .collect::<Vec<_>>();
                 Ok(Image::I16(ImageData::<i16>::from_data(
-                    width as usize,
-                    height as usize,
+                    width,
+                    height,
+                    zero_offset,
+                    scale,
+                    bayer_pattern,
+                    image_data,
+                )?))
+            }
+            Bitpix::I64 => {
+                let image_data = data
+                    .as_chunks::<8>()
+                    .0
+                    .iter()
+                    .map(|i| i64::from_be_bytes(*i))
+                    .collect::<Vec<_>>();
+                Ok(Image::I64(ImageData::<i64>::from_data(
+                    width,
+                    height,
                     zero_offset,
                     scale,
                     bayer_pattern,
@@ -180,8 +398,8 @@ impl Image {
                     .map(|i| i32::from_be_bytes(*i))
                     .collect::<Vec<_>>();
                 Ok(Image::I32(ImageData::<i32>::from_data(
-                    width as usize,
-                    height as usize,
+                    width,
+                    height,
                     zero_offset,
                     scale,
                     bayer_pattern,
@@ -190,6 +408,79 @@ impl Image {
             }
         }
     }
+
+    /// Decodes integers stored with the standard BZERO offset into their native type, see
+    /// [`Header::has_integer_offset`]
+    fn from_offset_data(
+        data: Vec<u8>,
+        width: usize,
+        height: usize,
+        bayer_pattern: Option<BayerPattern>,
+        header: &Header,
+    ) -> Result<Self> {
+        match header.bitpix() {
+            Bitpix::U8 => {
+                let image_data = data.iter().map(|i| (i ^ 0x80) as i8).collect::<Vec<_>>();
+                Ok(Image::I8(ImageData::<i8>::from_data(
+                    width,
+                    height,
+                    0.0,
+                    1.0,
+                    bayer_pattern,
+                    image_data,
+                )?))
+            }
+            Bitpix::I16 => {
+                let image_data = data
+                    .as_chunks::<2>()
+                    .0
+                    .iter()
+                    .map(|i| u16::from_be_bytes(*i) ^ 0x8000)
+                    .collect::<Vec<_>>();
+                Ok(Image::U16(ImageData::<u16>::from_data(
+                    width,
+                    height,
+                    0.0,
+                    1.0,
+                    bayer_pattern,
+                    image_data,
+                )?))
+            }
+            Bitpix::I32 => {
+                let image_data = data
+                    .as_chunks::<4>()
+                    .0
+                    .iter()
+                    .map(|i| u32::from_be_bytes(*i) ^ 0x8000_0000)
+                    .collect::<Vec<_>>();
+                Ok(Image::U32(ImageData::<u32>::from_data(
+                    width,
+                    height,
+                    0.0,
+                    1.0,
+                    bayer_pattern,
+                    image_data,
+                )?))
+            }
+            Bitpix::I64 => {
+                let image_data = data
+                    .as_chunks::<8>()
+                    .0
+                    .iter()
+                    .map(|i| u64::from_be_bytes(*i) ^ 0x8000_0000_0000_0000)
+                    .collect::<Vec<_>>();
+                Ok(Image::U64(ImageData::<u64>::from_data(
+                    width,
+                    height,
+                    0.0,
+                    1.0,
+                    bayer_pattern,
+                    image_data,
+                )?))
+            }
+            bitpix => Err(Error::UnsupportedBitpix(bitpix.into())),
+        }
+    }
 }
 
 #[cfg(feature = "image")]
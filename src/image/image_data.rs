@@ -1,7 +1,10 @@
+use crate::Result;
+use crate::error::Error;
 use crate::header::BayerPattern;
-use alloc::boxed::Box;
+use crate::image::Normalization;
+#[cfg(feature = "image")]
+use crate::image::demosaic::{self, DemosaicAlgorithm};
 use alloc::vec::Vec;
-use core::error::Error;
 use core::ops::Deref;
 use image::{ImageBuffer, Luma, Primitive, Rgb};
 
@@ -16,6 +19,13 @@ pub struct ImageData<T: Primitive> {
     // BSCALE
     scale: f64,
 
+    // DATAMIN and DATAMAX
+    data_min: Option<f64>,
+    data_max: Option<f64>,
+
+    // BLANK, in the same representation as the buffer values
+    blank: Option<i128>,
+
     bayer_pattern: Option<BayerPattern>,
     width: u32,
     height: u32,
@@ -29,19 +39,35 @@ impl<T: Primitive> ImageData<T> {
         scale: f64,
         bayer_pattern: Option<BayerPattern>,
         data: Vec<T>,
-    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+    ) -> Result<Self> {
         let buffer = ImageBuffer::<Luma<T>, Vec<T>>::from_raw(width as u32, height as u32, data)
-            .ok_or("Failed to construct image buffer")?;
+            .ok_or_else(|| Error::InvalidImage("Failed to construct image buffer".into()))?;
         Ok(Self {
             buffer,
             zero_offset,
             scale,
+            data_min: None,
+            data_max: None,
+            blank: None,
             bayer_pattern,
             width: width as u32,
             height: height as u32,
         })
     }
 
+    /// Sets the physical value range given by DATAMIN and DATAMAX
+    pub(crate) fn with_data_range(mut self, data_min: Option<f64>, data_max: Option<f64>) -> Self {
+        self.data_min = data_min;
+        self.data_max = data_max;
+        self
+    }
+
+    /// Sets the raw value that marks undefined pixels of integer images
+    pub(crate) fn with_blank(mut self, blank: Option<i128>) -> Self {
+        self.blank = blank;
+        self
+    }
+
     /// The camera bayer pattern or None if the camera is monochrome
     pub fn bayer_pattern(&self) -> &Option<BayerPattern> {
         &self.bayer_pattern
@@ -59,279 +85,184 @@ impl<T: Primitive> ImageData<T> {
 
     /// Returns the raw image data
     pub fn raw(&self) -> &[T] {
-        &self.buffer.as_raw()
+        self.buffer.as_raw()
     }
-}
 
-impl ImageData<f64> {
-    #[cfg(feature = "image")]
-    pub fn normalized_superpixel(
-        &self,
-    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>, Box<dyn Error + Send + Sync>> {
-        let bayer_pattern = self
-            .bayer_pattern
-            .ok_or_else(|| "Can not perform superpixel demosaic on a non rgb image")?;
-        let mut superpixel_image =
-            ImageBuffer::<Rgb<f64>, Vec<f64>>::new(self.width() / 2, self.height() / 2);
-        for (x, y, pixel) in superpixel_image.enumerate_pixels_mut() {
-            match bayer_pattern {
-                BayerPattern::RGGB => {
-                    let x = x * 2;
-                    let y = y * 2;
+    /// The physical value of a pixel, BZERO + BSCALE * raw value, cf. FITSv4, section 5.3. Null
+    /// pixels are NaN.
+    pub fn physical_pixel(&self, x: u32, y: u32) -> f64 {
+        self.to_physical(self.buffer.get_pixel(x, y)[0])
+    }
 
-                    let pixel_r = self.buffer.get_pixel(x, y)[0];
-                    let pixel_g1 = self.buffer.get_pixel(x + 1, y)[0];
-                    let pixel_g2 = self.buffer.get_pixel(x, y + 1)[0];
-                    let pixel_b = self.buffer.get_pixel(x + 1, y + 1)[0];
+    /// The physical value of a pixel, or None if the pixel is null
+    pub fn pixel(&self, x: u32, y: u32) -> Option<f64> {
+        Some(self.physical_pixel(x, y)).filter(|value| !value.is_nan())
+    }
 
-                    let pixel_g = (pixel_g1 + pixel_g2) / 2.0;
+    /// Whether a pixel is undefined, which is a raw value equal to BLANK for integer images and
+    /// NaN for float images, cf. FITSv4, section 5.3
+    pub fn is_null(&self, x: u32, y: u32) -> bool {
+        self.is_null_value(self.buffer.get_pixel(x, y)[0])
+    }
 
-                    pixel[0] = pixel_r;
-                    pixel[1] = pixel_g;
-                    pixel[2] = pixel_b;
-                }
-                _ => {}
-            }
-        }
-        Ok(superpixel_image)
+    /// For every pixel in row order, whether it holds a value
+    pub fn mask(&self) -> Vec<bool> {
+        self.buffer
+            .iter()
+            .map(|pixel| !self.is_null_value(*pixel))
+            .collect()
     }
 
+    /// The coordinates and physical values of all pixels that are not null
+    pub fn valid_pixels(&self) -> impl Iterator<Item = (u32, u32, f64)> + '_ {
+        self.buffer
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| !self.is_null_value(pixel[0]))
+            .map(|(x, y, pixel)| (x, y, self.to_physical(pixel[0])))
+    }
+
+    /// The physical values of all pixels, see [`Self::physical_pixel`]
     #[cfg(feature = "image")]
-    pub fn from_buffer(buffer: ImageBuffer<Luma<f64>, Vec<f64>>) -> Self {
-        let width = buffer.width();
-        let height = buffer.height();
-        Self {
-            buffer,
-            zero_offset: 0.0,
-            scale: 0.0,
-            bayer_pattern: None,
-            width,
-            height,
-        }
+    pub fn physical(&self) -> ImageBuffer<Luma<f64>, Vec<f64>> {
+        let data = self
+            .buffer
+            .iter()
+            .map(|pixel| self.to_physical(*pixel))
+            .collect();
+        ImageBuffer::from_raw(self.width, self.height, data).expect("a buffer of the same size")
+    }
+
+    /// The smallest and largest finite physical value, or None if there are no finite values
+    pub fn data_range(&self) -> Option<(f64, f64)> {
+        self.buffer
+            .iter()
+            .map(|pixel| self.to_physical(*pixel))
+            .filter(|value| value.is_finite())
+            .fold(None, |range, value| match range {
+                Some((min, max)) => Some((value.min(min), value.max(max))),
+                None => Some((value, value)),
+            })
     }
-}
 
-impl ImageData<f32> {
+    /// Physical values scaled into the range of 0.0 - 1.0, using DATAMIN and DATAMAX when the
+    /// header has them and the data range otherwise
     #[cfg(feature = "image")]
     pub fn normalized(&self) -> ImageBuffer<Luma<f64>, Vec<f64>> {
-        let mut normalized_image =
-            ImageBuffer::<Luma<f64>, Vec<f64>>::new(self.width(), self.height());
-        for (x, y, pixel) in self.buffer.enumerate_pixels() {
-            let pixel = pixel[0] as f64;
-            normalized_image.get_pixel_mut(x, y)[0] = pixel;
-        }
-        normalized_image
+        self.normalized_with(Normalization::default())
     }
 
+    /// Physical values scaled into the range of 0.0 - 1.0, values outside the chosen range are
+    /// clamped
     #[cfg(feature = "image")]
-    pub fn normalized_superpixel(
+    pub fn normalized_with(
         &self,
-    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>, Box<dyn Error + Send + Sync>> {
-        let bayer_pattern = self
-            .bayer_pattern
-            .ok_or_else(|| "Can not perform superpixel demosaic on a non rgb image")?;
-        let mut superpixel_image =
-            ImageBuffer::<Rgb<f64>, Vec<f64>>::new(self.width() / 2, self.height() / 2);
-        for (x, y, pixel) in superpixel_image.enumerate_pixels_mut() {
-            match bayer_pattern {
-                BayerPattern::RGGB => {
-                    let x = x * 2;
-                    let y = y * 2;
-
-                    let pixel_r = self.buffer.get_pixel(x, y)[0];
-                    let pixel_g1 = self.buffer.get_pixel(x + 1, y)[0];
-                    let pixel_g2 = self.buffer.get_pixel(x, y + 1)[0];
-                    let pixel_b = self.buffer.get_pixel(x + 1, y + 1)[0];
-
-                    let pixel_g = (pixel_g1 + pixel_g2) / 2.0;
-
-                    pixel[0] = pixel_r as f64;
-                    pixel[1] = pixel_g as f64;
-                    pixel[2] = pixel_b as f64;
-                }
-                _ => {}
-            }
+        normalization: Normalization,
+    ) -> ImageBuffer<Luma<f64>, Vec<f64>> {
+        let (min, max) = self.normalization_range(normalization);
+        let mut normalized = self.physical();
+        for pixel in normalized.iter_mut() {
+            *pixel = if max > min {
+                ((*pixel - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
         }
-        Ok(superpixel_image)
+        normalized
     }
-}
 
-impl ImageData<i32> {
+    /// Performs a superpixel demosaic of [`Self::normalized`]
     #[cfg(feature = "image")]
-    pub fn normalized(&self) -> ImageBuffer<Luma<f64>, Vec<f64>> {
-        let mut normalized_image =
-            ImageBuffer::<Luma<f64>, Vec<f64>>::new(self.width(), self.height());
-        for (x, y, pixel) in self.buffer.enumerate_pixels() {
-            let pixel = pixel[0] as f64;
-            let offset_pixel = pixel + self.zero_offset;
-            let scaled_pixel = offset_pixel * self.scale;
-            normalized_image.get_pixel_mut(x, y)[0] =
-                (i32::MAX as f64 + self.zero_offset) / scaled_pixel;
-        }
-        normalized_image
+    pub fn normalized_superpixel(&self) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>> {
+        self.normalized_superpixel_with(Normalization::default())
     }
 
+    /// Performs a superpixel demosaic of [`Self::normalized_with`], the result has half the width
+    /// and height of the image
     #[cfg(feature = "image")]
-    pub fn normalized_superpixel(
+    pub fn normalized_superpixel_with(
         &self,
-    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>, Box<dyn Error + Send + Sync>> {
-        let bayer_pattern = self
-            .bayer_pattern
-            .ok_or_else(|| "Can not perform superpixel demosaic on a non rgb image")?;
-        let mut superpixel_image =
-            ImageBuffer::<Rgb<f64>, Vec<f64>>::new(self.width() / 2, self.height() / 2);
-        for (x, y, pixel) in superpixel_image.enumerate_pixels_mut() {
-            match bayer_pattern {
-                BayerPattern::RGGB => {
-                    let x = x * 2;
-                    let y = y * 2;
-
-                    let pixel_r = self.buffer.get_pixel(x, y)[0];
-                    let pixel_g1 = self.buffer.get_pixel(x + 1, y)[0];
-                    let pixel_g2 = self.buffer.get_pixel(x, y + 1)[0];
-                    let pixel_b = self.buffer.get_pixel(x + 1, y + 1)[0];
-
-                    let pixel_r = pixel_r + self.zero_offset as i32;
-                    let pixel_r = pixel_r * self.scale as i32;
-
-                    let pixel_g1 = pixel_g1 + self.zero_offset as i32;
-                    let pixel_g1 = pixel_g1 * self.scale as i32;
-
-                    let pixel_g2 = pixel_g2 + self.zero_offset as i32;
-                    let pixel_g2 = pixel_g2 * self.scale as i32;
-
-                    let pixel_b = pixel_b + self.zero_offset as i32;
-                    let pixel_b = pixel_b * self.scale as i32;
-
-                    let pixel_g = (pixel_g1 + pixel_g2) / 2;
-
-                    pixel[0] = (i32::MAX as f64 + self.zero_offset) * pixel_r as f64;
-                    pixel[1] = (i32::MAX as f64 + self.zero_offset) * pixel_g as f64;
-                    pixel[2] = (i32::MAX as f64 + self.zero_offset) * pixel_b as f64;
-                }
-                _ => {}
-            }
-        }
-        Ok(superpixel_image)
+        normalization: Normalization,
+    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>> {
+        let bayer_pattern = self.bayer_pattern.ok_or_else(|| {
+            Error::InvalidImage("Can not perform superpixel demosaic on a non rgb image".into())
+        })?;
+        demosaic::binned_superpixel(&self.normalized_with(normalization), bayer_pattern)
     }
-}
 
-impl ImageData<i16> {
+    /// Demosaics [`Self::normalized`] into a color image of the same size
     #[cfg(feature = "image")]
-    pub fn normalized(&self) -> ImageBuffer<Luma<f64>, Vec<f64>> {
-        let mut normalized_image =
-            ImageBuffer::<Luma<f64>, Vec<f64>>::new(self.width(), self.height());
-        for (x, y, pixel) in self.buffer.enumerate_pixels() {
-            let mut pixel = pixel[0] as f64;
-            pixel += self.zero_offset;
-            pixel *= self.scale;
-            normalized_image.get_pixel_mut(x, y)[0] = pixel / (i16::MAX as f64 + self.zero_offset);
-        }
-        normalized_image
+    pub fn normalized_demosaic(
+        &self,
+        algorithm: DemosaicAlgorithm,
+    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>> {
+        self.normalized_demosaic_with(Normalization::default(), algorithm)
     }
 
+    /// Demosaics [`Self::normalized_with`] into a color image of the same size. Interpolated
+    /// values are clamped to 0.0 - 1.0.
     #[cfg(feature = "image")]
-    pub fn normalized_superpixel(
+    pub fn normalized_demosaic_with(
         &self,
-    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>, Box<dyn Error + Send + Sync>> {
+        normalization: Normalization,
+        algorithm: DemosaicAlgorithm,
+    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>> {
         let bayer_pattern = self
             .bayer_pattern
-            .ok_or_else(|| "Can not perform superpixel demosaic on a non rgb image")?;
-        let mut superpixel_image =
-            ImageBuffer::<Rgb<f64>, Vec<f64>>::new(self.width() / 2, self.height() / 2);
-        for (x, y, pixel) in superpixel_image.enumerate_pixels_mut() {
-            match bayer_pattern {
-                BayerPattern::RGGB => {
-                    let x = x * 2;
-                    let y = y * 2;
-
-                    let pixel_r = self.buffer.get_pixel(x, y)[0] as f64;
-                    let pixel_g1 = self.buffer.get_pixel(x + 1, y)[0] as f64;
-                    let pixel_g2 = self.buffer.get_pixel(x, y + 1)[0] as f64;
-                    let pixel_b = self.buffer.get_pixel(x + 1, y + 1)[0] as f64;
-
-                    let pixel_r = pixel_r + fix_zero_offset(self.zero_offset);
-                    let pixel_r = pixel_r * self.scale;
-
-                    let pixel_g1 = pixel_g1 + fix_zero_offset(self.zero_offset);
-                    let pixel_g1 = pixel_g1 * self.scale;
-
-                    let pixel_g2 = pixel_g2 + fix_zero_offset(self.zero_offset);
-                    let pixel_g2 = pixel_g2 * self.scale;
-
-                    let pixel_b = pixel_b + fix_zero_offset(self.zero_offset);
-                    let pixel_b = pixel_b * self.scale;
+            .ok_or_else(|| Error::InvalidImage("Can not demosaic a non rgb image".into()))?;
+        let mut rgb = demosaic::demosaic(
+            &self.normalized_with(normalization),
+            bayer_pattern,
+            algorithm,
+        )?;
+        for value in rgb.iter_mut() {
+            *value = value.clamp(0.0, 1.0);
+        }
+        Ok(rgb)
+    }
 
-                    let pixel_g = (pixel_g1 + pixel_g2) / 2.0;
+    fn normalization_range(&self, normalization: Normalization) -> (f64, f64) {
+        let data_range = || self.data_range().unwrap_or((0.0, 0.0));
+        match normalization {
+            Normalization::Keywords => match (self.data_min, self.data_max) {
+                (Some(min), Some(max)) => (min, max),
+                _ => data_range(),
+            },
+            Normalization::DataRange => data_range(),
+            Normalization::Range { min, max } => (min, max),
+        }
+    }
 
-                    pixel[0] = pixel_r / (i16::MAX as f64 + self.zero_offset);
-                    pixel[1] = pixel_g / (i16::MAX as f64 + self.zero_offset);
-                    pixel[2] = pixel_b / (i16::MAX as f64 + self.zero_offset);
-                }
-                _ => {}
-            }
+    fn to_physical(&self, raw: T) -> f64 {
+        if self.is_null_value(raw) {
+            return f64::NAN;
         }
-        Ok(superpixel_image)
+        self.zero_offset + self.scale * raw.to_f64().unwrap_or(f64::NAN)
     }
-}
 
-impl ImageData<u8> {
-    #[cfg(feature = "image")]
-    pub fn normalized(&self) -> ImageBuffer<Luma<f64>, Vec<f64>> {
-        let mut normalized_image =
-            ImageBuffer::<Luma<f64>, Vec<f64>>::new(self.width(), self.height());
-        for (x, y, pixel) in self.buffer.enumerate_pixels() {
-            let pixel = pixel[0] as f64;
-            let offset_pixel = pixel + self.zero_offset;
-            let scaled_pixel = offset_pixel * self.scale;
-            normalized_image.get_pixel_mut(x, y)[0] =
-                (u8::MAX as f64 + self.zero_offset) / scaled_pixel;
+    fn is_null_value(&self, raw: T) -> bool {
+        match self.blank {
+            Some(blank) => raw.to_i128() == Some(blank),
+            None => raw.to_f64().is_some_and(f64::is_nan),
         }
-        normalized_image
     }
+}
 
+impl ImageData<f64> {
     #[cfg(feature = "image")]
-    pub fn normalized_superpixel(
-        &self,
-    ) -> Result<ImageBuffer<Rgb<f64>, Vec<f64>>, Box<dyn Error + Send + Sync>> {
-        let bayer_pattern = self
-            .bayer_pattern
-            .ok_or_else(|| "Can not perform superpixel demosaic on a non rgb image")?;
-        let mut superpixel_image =
-            ImageBuffer::<Rgb<f64>, Vec<f64>>::new(self.width() / 2, self.height() / 2);
-        for (x, y, pixel) in superpixel_image.enumerate_pixels_mut() {
-            match bayer_pattern {
-                BayerPattern::RGGB => {
-                    let x = x * 2;
-                    let y = y * 2;
-
-                    let pixel_r = self.buffer.get_pixel(x, y)[0];
-                    let pixel_g1 = self.buffer.get_pixel(x + 1, y)[0];
-                    let pixel_g2 = self.buffer.get_pixel(x, y + 1)[0];
-                    let pixel_b = self.buffer.get_pixel(x + 1, y + 1)[0];
-
-                    let pixel_r = pixel_r + self.zero_offset as u8;
-                    let pixel_r = pixel_r * self.scale as u8;
-
-                    let pixel_g1 = pixel_g1 + self.zero_offset as u8;
-                    let pixel_g1 = pixel_g1 * self.scale as u8;
-
-                    let pixel_g2 = pixel_g2 + self.zero_offset as u8;
-                    let pixel_g2 = pixel_g2 * self.scale as u8;
-
-                    let pixel_b = pixel_b + self.zero_offset as u8;
-                    let pixel_b = pixel_b * self.scale as u8;
-
-                    let pixel_g = (pixel_g1 + pixel_g2) / 2;
-
-                    pixel[0] = (u8::MAX as f64 + self.zero_offset) * pixel_r as f64;
-                    pixel[1] = (u8::MAX as f64 + self.zero_offset) * pixel_g as f64;
-                    pixel[2] = (u8::MAX as f64 + self.zero_offset) * pixel_b as f64;
-                }
-                _ => {}
-            }
+    pub fn from_buffer(buffer: ImageBuffer<Luma<f64>, Vec<f64>>) -> Self {
+        let width = buffer.width();
+        let height = buffer.height();
+        Self {
+            buffer,
+            zero_offset: 0.0,
+            scale: 1.0,
+            data_min: None,
+            data_max: None,
+            blank: None,
+            bayer_pattern: None,
+            width,
+            height,
         }
-        Ok(superpixel_image)
     }
 }
 
@@ -342,11 +273,3 @@ impl<T: Primitive> Deref for ImageData<T> {
         &self.buffer
     }
 }
-
-fn fix_zero_offset(zero_offset: f64) -> f64 {
-    if zero_offset >= 0.0 {
-        zero_offset
-    } else {
-        zero_offset * -1.0
-    }
-}
@@ -1,7 +1,19 @@
 //! Struct for working with fits images
 
+#[cfg(feature = "image")]
+pub mod demosaic;
 mod image;
 mod image_data;
+mod nd_array;
+mod normalization;
+mod region;
 
+#[cfg(feature = "image")]
+pub use self::demosaic::DemosaicAlgorithm;
 pub use self::image::Image;
 pub use self::image_data::ImageData;
+pub use self::nd_array::NdArray;
+pub use self::normalization::Normalization;
+
+pub(crate) use self::nd_array::read_slice;
+pub(crate) use self::region::read_region;
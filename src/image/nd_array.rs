@@ -0,0 +1,224 @@
+use crate::Result;
+use crate::error::Error;
+use crate::header::{Bitpix, Header};
+use alloc::format;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A N-dimensional array in FITS order, where the first axis (NAXIS1) varies fastest
+#[derive(Debug, Clone, PartialEq)]
+pub struct NdArray<T> {
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+    data: Vec<T>,
+}
+
+impl<T> NdArray<T> {
+    pub fn from_data(shape: Vec<usize>, data: Vec<T>) -> Result<Self> {
+        let len = shape.iter().product::<usize>();
+        if data.len() != len {
+            return Err(Error::InvalidImage(format!(
+                "Array of shape {:?} needs {} values, got {}",
+                shape,
+                len,
+                data.len()
+            )));
+        }
+
+        Ok(Self {
+            strides: strides(&shape),
+            shape,
+            data,
+        })
+    }
+
+    /// The length of every axis, the first axis varies fastest
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    /// The distance in elements between neighbouring values along every axis
+    pub fn strides(&self) -> &[usize] {
+        &self.strides
+    }
+
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Vec<T> {
+        self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The value at the given index, with one entry per axis
+    pub fn get(&self, index: &[usize]) -> Option<&T> {
+        if index.len() != self.shape.len() || index.iter().zip(&self.shape).any(|(i, n)| i >= n) {
+            return None;
+        }
+        let offset = index
+            .iter()
+            .zip(&self.strides)
+            .map(|(i, s)| i * s)
+            .sum::<usize>();
+        self.data.get(offset)
+    }
+}
+
+fn strides(shape: &[usize]) -> Vec<usize> {
+    let mut stride = 1;
+    shape
+        .iter()
+        .map(|len| {
+            let current = stride;
+            stride *= len;
+            current
+        })
+        .collect()
+}
+
+/// Reads a hyperslab of the data unit described by `header` as physical values, with BLANK
+/// pixels as NaN. `read` returns the given number of bytes, starting at a byte offset into the data
+/// unit. Every run along the first axis is read with a single call.
+pub(crate) fn read_slice(
+    header: &Header,
+    ranges: &[Range<usize>],
+    mut read: impl FnMut(u64, usize) -> Result<Vec<u8>>,
+) -> Result<NdArray<f64>> {
+    let shape = header.data_shape();
+    if ranges.len() != shape.len() {
+        return Err(Error::InvalidImage(format!(
+            "Expected {} slice ranges, one for every axis, got {}",
+            shape.len(),
+            ranges.len()
+        )));
+    }
+    for (axis, (range, len)) in ranges.iter().zip(&shape).enumerate() {
+        if range.start > range.end || range.end > *len {
+            return Err(Error::InvalidImage(format!(
+                "Slice range {:?} is outside of axis {} with length {}",
+                range,
+                axis + 1,
+                len
+            )));
+        }
+    }
+
+    // A HDU without axes has no data, and an array without axes would hold one value
+    if shape.is_empty() {
+        return Ok(NdArray {
+            shape: Vec::new(),
+            strides: Vec::new(),
+            data: Vec::new(),
+        });
+    }
+
+    let out_shape = ranges.iter().map(|range| range.len()).collect::<Vec<_>>();
+    let len = out_shape.iter().product::<usize>();
+    let mut data = Vec::with_capacity(len);
+    if len == 0 {
+        return NdArray::from_data(out_shape, data);
+    }
+
+    let decoder = PhysicalDecoder::new(header);
+    let strides = strides(&shape);
+    let bytes_per_value = header.bitpix().byte_size();
+    let runs = len / out_shape[0];
+    for run in 0..runs {
+        // The position of this run along the other axes, the second axis varies fastest
+        let mut remaining = run;
+        let mut offset = ranges[0].start;
+        for axis in 1..shape.len() {
+            let position = remaining % out_shape[axis];
+            remaining /= out_shape[axis];
+            offset += (ranges[axis].start + position) * strides[axis];
+        }
+
+        let bytes = read(
+            (offset * bytes_per_value) as u64,
+            out_shape[0] * bytes_per_value,
+        )?;
+        decoder.decode(&bytes, &mut data);
+    }
+
+    NdArray::from_data(out_shape, data)
+}
+
+/// Turns raw big endian values into physical values, BZERO + BSCALE * raw value
+pub(crate) struct PhysicalDecoder {
+    bitpix: Bitpix,
+    zero: f64,
+    scale: f64,
+    blank: Option<i64>,
+}
+
+impl PhysicalDecoder {
+    pub(crate) fn new(header: &Header) -> Self {
+        Self {
+            bitpix: header.bitpix(),
+            zero: header.bzero().unwrap_or(0.0),
+            scale: header.bscale().unwrap_or(1.0),
+            blank: header.blank(),
+        }
+    }
+
+    pub(crate) fn decode(&self, bytes: &[u8], values: &mut Vec<f64>) {
+        match self.bitpix {
+            Bitpix::U8 => values.extend(bytes.iter().map(|raw| self.integer(*raw as i64))),
+            Bitpix::I16 => values.extend(
+                bytes
+                    .as_chunks::<2>()
+                    .0
+                    .iter()
+                    .map(|raw| self.integer(i16::from_be_bytes(*raw) as i64)),
+            ),
+            Bitpix::I32 => values.extend(
+                bytes
+                    .as_chunks::<4>()
+                    .0
+                    .iter()
+                    .map(|raw| self.integer(i32::from_be_bytes(*raw) as i64)),
+            ),
+            Bitpix::I64 => values.extend(
+                bytes
+                    .as_chunks::<8>()
+                    .0
+                    .iter()
+                    .map(|raw| self.integer(i64::from_be_bytes(*raw))),
+            ),
+            Bitpix::F32 => values.extend(
+                bytes
+                    .as_chunks::<4>()
+                    .0
+                    .iter()
+                    .map(|raw| self.float(f32::from_be_bytes(*raw) as f64)),
+            ),
+            Bitpix::F64 => values.extend(
+                bytes
+                    .as_chunks::<8>()
+                    .0
+                    .iter()
+                    .map(|raw| self.float(f64::from_be_bytes(*raw))),
+            ),
+        }
+    }
+
+    fn integer(&self, raw: i64) -> f64 {
+        if self.blank == Some(raw) {
+            f64::NAN
+        } else {
+            self.float(raw as f64)
+        }
+    }
+
+    fn float(&self, raw: f64) -> f64 {
+        self.zero + self.scale * raw
+    }
+}
@@ -0,0 +1,13 @@
+/// The range of physical values that normalization maps onto 0.0 - 1.0
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Normalization {
+    /// DATAMIN and DATAMAX from the header, or the data range when either is missing
+    #[default]
+    Keywords,
+
+    /// The smallest and largest finite physical value of the image
+    DataRange,
+
+    /// A fixed range of physical values
+    Range { min: f64, max: f64 },
+}
@@ -0,0 +1,66 @@
+use crate::Result;
+use crate::error::Error;
+use crate::header::Header;
+use crate::image::Image;
+use alloc::format;
+use alloc::vec::Vec;
+
+/// Reads every `step`th pixel of a rectangle within image plane `index`. `read` returns the given
+/// number of bytes, starting at a byte offset into the data unit, and is called once per row.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn read_region(
+    header: &Header,
+    index: usize,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    step: u32,
+    mut read: impl FnMut(u64, usize) -> Result<Vec<u8>>,
+) -> Result<Image> {
+    let images_width = header.naxis_n(0).unwrap_or(0).max(0) as u64;
+    let images_height = header.naxis_n(1).unwrap_or(0).max(0) as u64;
+    if step == 0 {
+        return Err(Error::InvalidImage("Region step must be at least 1".into()));
+    }
+    if x as u64 + width as u64 > images_width || y as u64 + height as u64 > images_height {
+        return Err(Error::InvalidImage(format!(
+            "Region {}x{} at ({}, {}) is outside of the {}x{} image",
+            width, height, x, y, images_width, images_height
+        )));
+    }
+
+    let bytes_per_pixel = header.bitpix().byte_size();
+    let region_width = width.div_ceil(step) as usize;
+    let region_height = height.div_ceil(step) as usize;
+    let plane_offset = index as u64 * images_width * images_height;
+    // The bytes from the first to the last pixel that is kept in a row
+    let row_len = match region_width {
+        0 => 0,
+        n => ((n - 1) * step as usize + 1) * bytes_per_pixel,
+    };
+
+    let mut data = Vec::with_capacity(region_width * region_height * bytes_per_pixel);
+    for row in (y..y + height).step_by(step as usize) {
+        if row_len == 0 {
+            break;
+        }
+        let offset = (plane_offset + row as u64 * images_width + x as u64) * bytes_per_pixel as u64;
+        let bytes = read(offset, row_len)?;
+        if step == 1 {
+            data.extend_from_slice(&bytes);
+        } else {
+            for pixel in bytes.chunks(bytes_per_pixel).step_by(step as usize) {
+                data.extend_from_slice(pixel);
+            }
+        }
+    }
+
+    // Every other column or row drops colors of the bayer pattern, so the region is monochrome
+    let bayer_pattern = header
+        .bayer_pattern()
+        .filter(|_| step % 2 == 1)
+        .map(|pattern| pattern.shifted(x, y));
+
+    Image::from_region_data(data, region_width, region_height, bayer_pattern, header)
+}
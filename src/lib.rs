@@ -9,6 +9,7 @@ extern crate alloc;
 
 pub mod ansi_table;
 pub mod bin_table;
+mod card_position;
 mod error;
 mod fits;
 mod fits_slice;
@@ -17,13 +18,19 @@ pub mod fs;
 pub mod hdu;
 pub mod header;
 pub mod image;
+mod open_options;
+mod parse_warning;
 mod result;
 mod slice_ascii_table_hdu;
 mod slice_bin_table_hdu;
 mod slice_image_hdu;
+mod template;
 mod util;
 
+pub use self::card_position::CardPosition;
 pub use self::error::Error;
 pub use self::fits::Fits;
 pub use self::fits_slice::FitsSlice;
+pub use self::open_options::OpenOptions;
+pub use self::parse_warning::ParseWarning;
 pub use self::result::Result;
@@ -0,0 +1,58 @@
+use crate::Result;
+use crate::error::Error;
+use crate::fits_slice::FitsSlice;
+#[cfg(feature = "fs")]
+use crate::fs::FsFits;
+use crate::parse_warning::ParseWarning;
+use alloc::vec::Vec;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Options for reading FITS files.
+///
+/// In strict mode any card that can not be parsed, and any violation of the mandatory keyword
+/// rules of FITSv4, section 4.4.1, is an error. In lenient mode, the default, bad cards are kept as
+/// raw text and the problems are returned as warnings. Problems that make it impossible to locate
+/// the data of a HDU are an error in both modes.
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    strict: bool,
+}
+
+impl OpenOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether files have to follow the FITS standard strictly
+    pub fn strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Opens a FITS file, returning it together with the problems found in lenient mode
+    #[cfg(feature = "fs")]
+    pub fn open(&self, path: &Path) -> Result<(FsFits, Vec<ParseWarning>)> {
+        FsFits::open_with(path, self)
+    }
+
+    /// Parses a complete FITS file held in memory, returning it together with the problems found
+    /// in lenient mode
+    pub fn open_slice(&self, data: &[u8]) -> Result<(FitsSlice, Vec<ParseWarning>)> {
+        FitsSlice::from_slice_with(data, self)
+    }
+
+    /// Records a problem, which is an error in strict mode
+    pub(crate) fn report(
+        &self,
+        warnings: &mut Vec<ParseWarning>,
+        warning: ParseWarning,
+    ) -> Result<()> {
+        if self.strict {
+            Err(Error::InvalidHeader(warning))
+        } else {
+            warnings.push(warning);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+
+/// A problem found while reading a file in lenient mode, see [`crate::OpenOptions`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    /// Index of the HDU, the primary HDU is 0
+    pub hdu: usize,
+    /// Index of the 80 character record within the header, if the problem concerns a single
+    /// card. Every CONTINUE record counts, so this is the position of the card in the file.
+    pub card: Option<usize>,
+    /// The keyword the problem concerns
+    pub keyword: String,
+    pub problem: String,
+}
+
+impl Display for ParseWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "HDU {}", self.hdu)?;
+        if let Some(card) = self.card {
+            write!(f, ", card {}", card)?;
+        }
+        if !self.keyword.is_empty() {
+            write!(f, " ({})", self.keyword)?;
+        }
+        write!(f, ": {}", self.problem)
+    }
+}
@@ -1 +1 @@
-pub type Result<T> = std::result::Result<T, crate::error::Error>;
+pub type Result<T> = core::result::Result<T, crate::error::Error>;
@@ -1,29 +1,42 @@
+use crate::Result;
 use crate::bin_table::{BinTable, Row};
 use crate::hdu::{AsciiTableHDU, HDU};
 use crate::header::Header;
 use futures::stream::BoxStream;
-use std::error::Error;
-use std::prelude::rust_2015::Box;
+use std::prelude::rust_2015::Vec;
 
 #[derive(Debug, Clone)]
-pub struct SliceAsciiTableHDU {}
+pub struct SliceAsciiTableHDU {
+    header: Header,
+    data: Vec<u8>,
+}
+
+impl SliceAsciiTableHDU {
+    pub(crate) fn new(header: Header, data: Vec<u8>) -> Self {
+        Self { header, data }
+    }
+}
 
 impl HDU for SliceAsciiTableHDU {
     fn header(&self) -> &Header {
-        todo!()
+        &self.header
     }
 
     fn header_mut(&mut self) -> &mut Header {
-        todo!()
+        &mut self.header
+    }
+
+    fn read_raw_data(&self) -> Result<Vec<u8>> {
+        Ok(self.data.clone())
     }
 }
 
 impl AsciiTableHDU for SliceAsciiTableHDU {
-    fn read_table(&self) -> Result<BinTable, Box<dyn Error + Send + Sync>> {
+    fn read_table(&self) -> Result<BinTable> {
         todo!()
     }
 
-    fn stream_table_rows(&self) -> Result<BoxStream<'_, Row<'_>>, Box<dyn Error + Send + Sync>> {
+    fn stream_table_rows(&self) -> Result<BoxStream<'_, Row<'_>>> {
         todo!()
     }
 }
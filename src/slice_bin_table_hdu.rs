@@ -1,53 +1,63 @@
+use crate::Result;
 use crate::bin_table::{BinTable, Row};
 use crate::hdu::{BinTableHDU, HDU};
 use crate::header::Header;
 use futures::stream::BoxStream;
 #[cfg(feature = "serde")]
 use serde::de::DeserializeOwned;
-use std::error::Error;
-use std::prelude::rust_2015::{Box, Vec};
+use std::prelude::rust_2015::Vec;
 
 #[derive(Debug, Clone)]
-pub struct SliceBinTableHDU {}
+pub struct SliceBinTableHDU {
+    header: Header,
+    data: Vec<u8>,
+}
+
+impl SliceBinTableHDU {
+    pub(crate) fn new(header: Header, data: Vec<u8>) -> Self {
+        Self { header, data }
+    }
+}
 
 impl HDU for SliceBinTableHDU {
     fn header(&self) -> &Header {
-        todo!()
+        &self.header
     }
 
     fn header_mut(&mut self) -> &mut Header {
-        todo!()
+        &mut self.header
+    }
+
+    fn read_raw_data(&self) -> Result<Vec<u8>> {
+        Ok(self.data.clone())
     }
 }
 
 impl BinTableHDU for SliceBinTableHDU {
     fn table_data_bytes_len(&self) -> u64 {
-        todo!()
+        (self.header.naxis_n(0).unwrap_or(0) * self.header.naxis_n(1).unwrap_or(0)) as u64
     }
 
-    fn read_table(&self) -> Result<BinTable, Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn read_table(&self) -> Result<BinTable> {
+        let bytes = self.data[..self.table_data_bytes_len() as usize].to_vec();
+
+        BinTable::from_u8(&self.header, bytes)
     }
 
     #[cfg(feature = "serde")]
-    fn read_rows<T: DeserializeOwned + Send + Sync>(
-        &self,
-    ) -> Result<Vec<T>, Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn read_rows<T: DeserializeOwned + Send + Sync>(&self) -> Result<Vec<T>> {
+        let table = self.read_table()?;
+        crate::bin_table::from_bin_table(&table)
     }
 
     #[cfg(feature = "tokio")]
-    fn stream_table_rows_raw(
-        &self,
-    ) -> Result<BoxStream<'_, Row<'_>>, Box<dyn Error + Send + Sync>> {
+    fn stream_table_rows_raw(&self) -> Result<BoxStream<'_, Row<'_>>> {
         todo!()
     }
 
     #[cfg(feature = "serde")]
     #[cfg(feature = "tokio")]
-    fn stream_table_rows<T: DeserializeOwned + Send + Sync>(
-        &self,
-    ) -> Result<BoxStream<'_, T>, Box<dyn Error + Send + Sync>> {
+    fn stream_table_rows<T: DeserializeOwned + Send + Sync>(&self) -> Result<BoxStream<'_, T>> {
         todo!()
     }
 }
@@ -1,110 +1,213 @@
+use crate::Result;
 use crate::hdu::{HDU, ImageHDU};
-use crate::header::{BayerPattern, Header, ImageType};
-use crate::image::Image;
-use std::error::Error;
-use std::prelude::rust_2015::Box;
+use crate::header::{BayerPattern, Bitpix, Header, ImageType};
+use crate::image::{Image, NdArray, read_region, read_slice};
+use crate::util::images_to_be_bytes;
+use alloc::vec;
+use core::ops::Range;
+use std::prelude::rust_2015::Vec;
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
-pub struct SliceImageHDU {}
+pub struct SliceImageHDU {
+    header: Header,
+    data: Vec<u8>,
+}
+
+impl SliceImageHDU {
+    pub(crate) fn new(header: Header, data: Vec<u8>) -> Self {
+        Self { header, data }
+    }
+
+    fn set_data(
+        &mut self,
+        bitpix: Bitpix,
+        integer_offset: bool,
+        width: u32,
+        height: u32,
+        count: usize,
+        data: Vec<u8>,
+    ) {
+        let axes = if count > 1 {
+            vec![width as u64, height as u64, count as u64]
+        } else {
+            vec![width as u64, height as u64]
+        };
+        let previous = self.header.bitpix();
+        self.header.set_data_layout(bitpix, &axes);
+        self.header.set_integer_offset(integer_offset, previous);
+        self.data = data;
+    }
+}
 
 impl HDU for SliceImageHDU {
     fn header(&self) -> &Header {
-        todo!()
+        &self.header
     }
 
     fn header_mut(&mut self) -> &mut Header {
-        todo!()
+        &mut self.header
+    }
+
+    fn read_raw_data(&self) -> Result<Vec<u8>> {
+        Ok(self.data.clone())
     }
 }
 
 impl ImageHDU for SliceImageHDU {
     fn image_count(&self) -> usize {
-        todo!()
+        self.header.data_shape().iter().skip(2).product()
     }
 
     fn images_width(&self) -> u32 {
-        todo!()
+        self.header.naxis_n(0).unwrap_or(0) as u32
     }
 
     fn images_height(&self) -> u32 {
-        todo!()
+        self.header.naxis_n(1).unwrap_or(0) as u32
     }
 
     fn images_bayer_pattern(&self) -> Option<BayerPattern> {
-        todo!()
+        self.header.bayer_pattern()
     }
 
     fn images_type(&self) -> Option<&ImageType> {
-        todo!()
+        self.header.image_type()
     }
 
     fn images_exposure_time(&self) -> Option<Duration> {
-        todo!()
+        self.header
+            .exposure()
+            .or_else(|| self.header.exposure_time())
     }
 
-    fn read_image(&self, _index: usize) -> Result<Option<Image>, Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn read_image(&self, index: usize) -> Result<Option<Image>> {
+        if index >= self.image_count() || self.header.naxis() < 2 {
+            return Ok(None);
+        }
+
+        let size = self.image_data_size() as usize;
+        let start = size * index;
+        let bytes = self.data[start..start + size].to_vec();
+
+        let image = Image::from_data_and_header(bytes, &self.header)?;
+
+        Ok(Some(image))
     }
 
-    fn clear_images(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn read_decimated_region(
+        &self,
+        index: usize,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        step: u32,
+    ) -> Result<Option<Image>> {
+        if index >= self.image_count() || self.header.naxis() < 2 {
+            return Ok(None);
+        }
+
+        let image = read_region(
+            &self.header,
+            index,
+            x,
+            y,
+            width,
+            height,
+            step,
+            |offset, len| {
+                let start = offset as usize;
+                Ok(self.data[start..start + len].to_vec())
+            },
+        )?;
+        Ok(Some(image))
     }
 
-    fn set_raw_images_u8(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[u8]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn read_slice(&self, ranges: &[Range<usize>]) -> Result<NdArray<f64>> {
+        read_slice(&self.header, ranges, |offset, len| {
+            let start = offset as usize;
+            Ok(self.data[start..start + len].to_vec())
+        })
     }
 
-    fn set_raw_images_i16(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[i16]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn clear_images(&mut self) -> Result<()> {
+        let bitpix = self.header.bitpix();
+        self.header.set_data_layout(bitpix, &[]);
+        self.data = vec![];
+        Ok(())
     }
 
-    fn set_raw_images_i32(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[i32]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn set_raw_images_u8(&mut self, width: u32, height: u32, images: &[&[u8]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::U8, false, width, height, images.len(), data);
+        Ok(())
     }
 
-    fn set_raw_images_f32(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[f32]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn set_raw_images_i8(&mut self, width: u32, height: u32, images: &[&[i8]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::U8, true, width, height, images.len(), data);
+        Ok(())
     }
 
-    fn set_raw_images_f64(
-        &mut self,
-        _width: u32,
-        _height: u32,
-        _images: &[&[f64]],
-    ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        todo!()
+    fn set_raw_images_i16(&mut self, width: u32, height: u32, images: &[&[i16]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I16, false, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_u16(&mut self, width: u32, height: u32, images: &[&[u16]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I16, true, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_i32(&mut self, width: u32, height: u32, images: &[&[i32]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I32, false, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_u32(&mut self, width: u32, height: u32, images: &[&[u32]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I32, true, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_i64(&mut self, width: u32, height: u32, images: &[&[i64]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I64, false, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_u64(&mut self, width: u32, height: u32, images: &[&[u64]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::I64, true, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_f32(&mut self, width: u32, height: u32, images: &[&[f32]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::F32, false, width, height, images.len(), data);
+        Ok(())
+    }
+
+    fn set_raw_images_f64(&mut self, width: u32, height: u32, images: &[&[f64]]) -> Result<()> {
+        let data = images_to_be_bytes(width, height, images)?;
+        self.set_data(Bitpix::F64, false, width, height, images.len(), data);
+        Ok(())
     }
 
     fn stream_normalised_image(
         &self,
         _index: usize,
-    ) -> Result<Option<futures::stream::BoxStream<'_, (u32, u32, f64)>>, Box<dyn Error + Send + Sync>>
-    {
+    ) -> Result<Option<futures::stream::BoxStream<'_, (u32, u32, f64)>>> {
         todo!()
     }
 
     fn image_data_size(&self) -> u64 {
-        todo!()
+        self.images_width() as u64
+            * self.images_height() as u64
+            * self.header.bitpix().byte_size() as u64
     }
 }
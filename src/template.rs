@@ -0,0 +1,328 @@
+use crate::Result;
+use crate::error::Error;
+use crate::header::{Bitpix, Card, ExtensionType, Header, Value};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A HDU of a template, with the line it starts on for error messages
+struct TemplateHdu {
+    line: usize,
+    cards: Vec<Card>,
+}
+
+/// Parses a CFITSIO style header template into the headers of all HDUs it describes.
+///
+/// Every line holds a card as `KEYWORD = value / comment`, where the equals sign and the quotes
+/// around strings may be left out. Lines starting with `#` are ignored, `\END` ends the template,
+/// and a `#` in a keyword is replaced by a counter, e.g. `TTYPE#`. Each XTENSION card starts a new
+/// extension HDU. Missing mandatory keywords are added, NAXIS1 and TFIELDS of binary tables are
+/// computed from the TFORMn keywords.
+pub(crate) fn parse_template(text: &str) -> Result<Vec<Header>> {
+    let mut hdus = vec![TemplateHdu {
+        line: 1,
+        cards: vec![],
+    }];
+    let mut counters = BTreeMap::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(directive) = line.strip_prefix('\\') {
+            if directive.eq_ignore_ascii_case("END") {
+                break;
+            }
+            return Err(invalid_template(
+                number,
+                "template directive is not supported",
+            ));
+        }
+
+        let card =
+            parse_line(line, &mut counters).map_err(|message| invalid_template(number, message))?;
+        if matches!(card, Card::Xtension { .. }) {
+            counters.clear();
+            hdus.push(TemplateHdu {
+                line: number,
+                cards: vec![],
+            });
+        }
+        hdus.last_mut().expect("a HDU").cards.push(card);
+    }
+
+    hdus.into_iter()
+        .enumerate()
+        .map(|(index, hdu)| {
+            build_header(index == 0, hdu.cards)
+                .map_err(|message| invalid_template(hdu.line, message))
+        })
+        .collect()
+}
+
+fn parse_line(
+    line: &str,
+    counters: &mut BTreeMap<String, usize>,
+) -> core::result::Result<Card, String> {
+    let end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let (keyword, rest) = line.split_at(end);
+    let mut keyword = keyword.to_ascii_uppercase();
+
+    match keyword.as_str() {
+        "COMMENT" | "HISTORY" => {
+            let text = rest.trim();
+            if text.len() > 72 {
+                return Err("text is longer than 72 characters".into());
+            }
+            return Ok(if keyword == "COMMENT" {
+                Card::Comment(text.into())
+            } else {
+                Card::History(text.into())
+            });
+        }
+        _ => {}
+    }
+
+    if let Some(position) = keyword.find('#') {
+        let counter = counters.entry(keyword.clone()).or_insert(0);
+        *counter += 1;
+        keyword.replace_range(position..position + 1, &counter.to_string());
+    }
+
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim_start();
+    let (value, rest) = parse_value(rest)?;
+    let rest = rest.trim();
+    let comment = match rest.strip_prefix('/') {
+        Some(comment) => Some(comment.trim().to_string()).filter(|comment| !comment.is_empty()),
+        None if rest.is_empty() => None,
+        None => return Err(format!("unexpected text after the value: {}", rest)),
+    };
+
+    let value = value.with_comment(comment);
+    Card::new(&keyword, value.clone())
+        .or_else(|err| match value {
+            // Templates do not need a decimal point for whole numbers of float keywords
+            Value::Integer { value, comment } => Card::new(
+                &keyword,
+                Value::Float {
+                    value: value as f64,
+                    comment,
+                },
+            ),
+            _ => Err(err),
+        })
+        .map_err(|err| err.to_string())
+}
+
+/// Parses a quoted or bare value, returning it with the rest of the line
+fn parse_value(text: &str) -> core::result::Result<(Value, &str), String> {
+    if let Some(quoted) = text.strip_prefix('\'') {
+        let mut string = String::new();
+        let mut chars = quoted.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            if c != '\'' {
+                string.push(c);
+            } else if chars.next_if(|(_, next)| *next == '\'').is_some() {
+                string.push('\'');
+            } else {
+                let value = Value::String {
+                    value: string.trim_end().into(),
+                    comment: None,
+                };
+                return Ok((value, &quoted[index + 1..]));
+            }
+        }
+        return Err("string is missing its closing quote".into());
+    }
+
+    let end = text
+        .find(|c: char| c.is_whitespace() || c == '/')
+        .unwrap_or(text.len());
+    let (token, rest) = text.split_at(end);
+    Ok((infer_value(token), rest))
+}
+
+fn infer_value(token: &str) -> Value {
+    let comment = None;
+    if token.is_empty() {
+        Value::Undefined
+    } else if token == "T" || token == "F" {
+        Value::Logical {
+            value: token == "T",
+            comment,
+        }
+    } else if let Ok(value) = token.parse::<i64>() {
+        Value::Integer { value, comment }
+    } else if let Ok(value) = token.replace(['D', 'd'], "E").parse::<f64>()
+        && token.bytes().any(|b| b.is_ascii_digit())
+    {
+        Value::Float { value, comment }
+    } else {
+        Value::String {
+            value: token.into(),
+            comment,
+        }
+    }
+}
+
+/// Puts the mandatory keywords of a HDU first and in order, filling in those that are missing
+fn build_header(primary: bool, mut cards: Vec<Card>) -> core::result::Result<Header, String> {
+    let extension = match take(&mut cards, "XTENSION") {
+        Some(Card::Xtension { value, comment }) => Some((value, comment)),
+        _ if primary => None,
+        _ => return Err("extension is missing the XTENSION keyword".into()),
+    };
+    let simple_comment = take(&mut cards, "SIMPLE").and_then(|card| card.comment());
+
+    let bitpix = match take(&mut cards, "BITPIX") {
+        Some(Card::Bitpix { value, comment }) => Card::Bitpix { value, comment },
+        _ => Card::Bitpix {
+            value: Bitpix::U8,
+            comment: Some("number of bits per data pixel".into()),
+        },
+    };
+    let (declared_naxis, naxis_comment) = match take(&mut cards, "NAXIS") {
+        Some(Card::NAxis { value, comment }) if (0..=999).contains(&value) => {
+            (Some(value as usize), comment)
+        }
+        Some(Card::NAxis { value, .. }) => {
+            return Err(format!("NAXIS must be between 0 and 999, was {}", value));
+        }
+        _ => (None, None),
+    };
+
+    let mut axes: BTreeMap<usize, Card> = BTreeMap::new();
+    while let Some(position) = cards
+        .iter()
+        .position(|card| matches!(card, Card::NAxisN { .. }))
+    {
+        if let Card::NAxisN { index, .. } = &cards[position] {
+            axes.insert(*index, cards.remove(position));
+        }
+    }
+    let pcount = take(&mut cards, "PCOUNT");
+    let gcount = take(&mut cards, "GCOUNT");
+    let tfields_comment = take(&mut cards, "TFIELDS").and_then(|card| card.comment());
+
+    let table = match &extension {
+        Some((table @ (ExtensionType::BinTable | ExtensionType::AsciiTable), _)) => Some(*table),
+        _ => None,
+    };
+    if table.is_some() {
+        if !matches!(
+            bitpix,
+            Card::Bitpix {
+                value: Bitpix::U8,
+                ..
+            }
+        ) {
+            return Err("BITPIX must be 8 for tables".into());
+        }
+        let row_length = match table {
+            Some(ExtensionType::BinTable) => cards
+                .iter()
+                .map(|card| match card {
+                    Card::TableFormatN { value, .. } => value.bytes_len(),
+                    _ => 0,
+                })
+                .sum::<usize>(),
+            _ => match axes.get(&0) {
+                Some(Card::NAxisN { value, .. }) => *value as usize,
+                _ => return Err("ASCII tables need NAXIS1, the length of a row".into()),
+            },
+        };
+        let row_comment = axes.get(&0).and_then(Card::comment);
+        axes.insert(
+            0,
+            Card::NAxisN {
+                index: 0,
+                value: row_length as i64,
+                comment: row_comment.or(Some("width of table in bytes".into())),
+            },
+        );
+        axes.entry(1).or_insert(Card::NAxisN {
+            index: 1,
+            value: 0,
+            comment: Some("number of rows in table".into()),
+        });
+    }
+
+    // Axes declared by NAXIS but not given are filled in with a length of 0 below
+    let naxis = axes.keys().next_back().map_or(0, |index| index + 1);
+    let naxis = match declared_naxis {
+        Some(declared) if declared < naxis => {
+            return Err(format!(
+                "NAXIS is {} but the template sets NAXIS{}",
+                declared, naxis
+            ));
+        }
+        Some(declared) => declared,
+        None => naxis,
+    };
+    let mut header = vec![];
+    match &extension {
+        Some((value, comment)) => header.push(Card::Xtension {
+            value: *value,
+            comment: comment.clone(),
+        }),
+        None => header.push(Card::Simple {
+            value: true,
+            comment: simple_comment.or(Some("file does conform to FITS standard".into())),
+        }),
+    }
+    header.push(bitpix);
+    header.push(Card::NAxis {
+        value: naxis as i64,
+        comment: naxis_comment.or(Some("number of data axes".into())),
+    });
+    for index in 0..naxis {
+        header.push(axes.remove(&index).unwrap_or(Card::NAxisN {
+            index,
+            value: 0,
+            comment: Some(format!("length of data axis {}", index + 1)),
+        }));
+    }
+    if extension.is_some() {
+        header.push(pcount.unwrap_or(Card::ParameterCount {
+            value: 0,
+            comment: Some("number of parameters".into()),
+        }));
+        header.push(gcount.unwrap_or(Card::GroupCount {
+            value: 1,
+            comment: Some("number of groups".into()),
+        }));
+    }
+    if table.is_some() {
+        let fields = cards
+            .iter()
+            .filter(|card| card.key().starts_with("TFORM"))
+            .count();
+        header.push(Card::TableFields {
+            value: fields as i64,
+            comment: tfields_comment.or(Some("number of table fields".into())),
+        });
+    }
+    header.extend(cards.into_iter().filter(|card| *card != Card::End));
+
+    Ok(Header::from_cards(header))
+}
+
+/// Removes the first card with the given keyword
+fn take(cards: &mut Vec<Card>, key: &str) -> Option<Card> {
+    let position = cards.iter().position(|card| card.key() == key)?;
+    Some(cards.remove(position))
+}
+
+fn invalid_template(line: usize, message: impl Into<String>) -> Error {
+    Error::InvalidTemplate {
+        line,
+        message: message.into(),
+    }
+}
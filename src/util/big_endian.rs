@@ -0,0 +1,74 @@
+use crate::Result;
+use crate::error::Error;
+use alloc::format;
+use alloc::vec::Vec;
+
+/// Pixel types that can be stored in a FITS data unit
+pub(crate) trait BigEndian: Copy {
+    fn extend_be_bytes(self, data: &mut Vec<u8>);
+}
+
+macro_rules! impl_big_endian {
+    ($($ty:ty),*) => {
+        $(
+            impl BigEndian for $ty {
+                fn extend_be_bytes(self, data: &mut Vec<u8>) {
+                    data.extend_from_slice(&self.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_big_endian!(u8, i16, i32, i64, f32, f64);
+
+/// Types stored as the integer type of the other signedness, shifted by BZERO. Flipping the sign
+/// bit is the same as subtracting the offset, cf. FITSv4, section 5.2.5
+macro_rules! impl_big_endian_offset {
+    ($($ty:ty => $stored:ty),*) => {
+        $(
+            impl BigEndian for $ty {
+                fn extend_be_bytes(self, data: &mut Vec<u8>) {
+                    let stored = (self as $stored) ^ <$stored>::MIN;
+                    data.extend_from_slice(&stored.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_big_endian_offset!(u16 => i16, u32 => i32, u64 => i64);
+
+impl BigEndian for i8 {
+    fn extend_be_bytes(self, data: &mut Vec<u8>) {
+        data.push((self as u8) ^ 0x80);
+    }
+}
+
+/// Encodes a stack of equally sized images into a single big endian data array
+pub(crate) fn images_to_be_bytes<T: BigEndian>(
+    width: u32,
+    height: u32,
+    images: &[&[T]],
+) -> Result<Vec<u8>> {
+    let pixels = width as usize * height as usize;
+    let mut data = Vec::with_capacity(pixels * images.len() * size_of::<T>());
+
+    for (index, image) in images.iter().enumerate() {
+        if image.len() != pixels {
+            return Err(Error::InvalidImage(format!(
+                "Image {} has {} pixels, expected {}x{} = {} pixels",
+                index,
+                image.len(),
+                width,
+                height,
+                pixels
+            )));
+        }
+        for pixel in image.iter() {
+            pixel.extend_be_bytes(&mut data);
+        }
+    }
+
+    Ok(data)
+}
@@ -0,0 +1,58 @@
+use alloc::string::String;
+
+/// Adds the big endian 32 bit words of `data` to `initial` using ones' complement arithmetic, cf.
+/// the FITS checksum convention, section 3. The length of `data` must be a multiple of 4, which
+/// all FITS blocks are.
+pub(crate) fn ones_complement_sum(data: &[u8], initial: u32) -> u32 {
+    let mut sum = initial as u64;
+    for word in data.as_chunks::<4>().0 {
+        sum += u32::from_be_bytes(*word) as u64;
+    }
+    // Fold the carries back into the low bits
+    while sum >> 32 != 0 {
+        sum = (sum & 0xffff_ffff) + (sum >> 32);
+    }
+    sum as u32
+}
+
+/// Encodes the complement of a checksum as the 16 character ASCII string stored in the CHECKSUM
+/// keyword, cf. the FITS checksum convention, section 4. Replacing the sixteen `0` characters of
+/// the initial CHECKSUM value with this string makes the HDU sum to negative zero.
+pub(crate) fn encode_checksum(sum: u32) -> String {
+    // Punctuation between the digits and the letters is avoided in the encoded string
+    const EXCLUDE: [u8; 13] = [
+        0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f, 0x40, 0x5b, 0x5c, 0x5d, 0x5e, 0x5f, 0x60,
+    ];
+
+    let value = !sum;
+    let mut ascii = [0_u8; 16];
+    for (i, byte) in value.to_be_bytes().iter().enumerate() {
+        let quotient = byte / 4 + b'0';
+        let remainder = byte % 4;
+        let mut ch = [quotient; 4];
+        ch[0] += remainder;
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for excluded in EXCLUDE {
+                for j in [0, 2] {
+                    if ch[j] == excluded || ch[j + 1] == excluded {
+                        ch[j] += 1;
+                        ch[j + 1] -= 1;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        for (j, ch) in ch.iter().enumerate() {
+            ascii[4 * j + i] = *ch;
+        }
+    }
+
+    // The string is rotated one character to the right, so it lines up with the 4 byte words of
+    // the record it is stored in
+    ascii.rotate_right(1);
+    ascii.iter().map(|ch| *ch as char).collect()
+}
@@ -1,9 +1,15 @@
+mod big_endian;
+mod checksum;
 mod read_bytes;
 mod read_seek;
+mod write_hdu;
 
 #[cfg(feature = "tokio")]
 mod read_bytes_async;
 
+pub(crate) use self::big_endian::images_to_be_bytes;
+pub(crate) use self::checksum::{encode_checksum, ones_complement_sum};
 pub(crate) use self::read_bytes::read_bytes;
 pub(crate) use self::read_bytes_async::read_bytes_async;
 pub(crate) use self::read_seek::ReadSeek;
+pub(crate) use self::write_hdu::{padded_data, write_hdu};
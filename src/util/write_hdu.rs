@@ -0,0 +1,33 @@
+use crate::Result;
+use crate::hdu::HDU;
+use crate::header::ExtensionType;
+use alloc::vec::Vec;
+
+/// Appends the header and data unit of a HDU to `output`, both padded to whole FITS blocks. The
+/// CHECKSUM and DATASUM keywords of the written header are brought up to date when needed.
+pub(crate) fn write_hdu<H: HDU + ?Sized>(output: &mut Vec<u8>, hdu: &H) -> Result<()> {
+    let data = padded_data(hdu)?;
+    let mut header = hdu.header().clone();
+    header.refresh_checksum(&data);
+
+    output.extend_from_slice(&header.to_bytes());
+    output.extend_from_slice(&data);
+
+    Ok(())
+}
+
+/// Reads the data unit of a HDU, padded to whole FITS blocks
+pub(crate) fn padded_data<H: HDU + ?Sized>(hdu: &H) -> Result<Vec<u8>> {
+    let mut data = hdu.read_raw_data()?;
+
+    // ASCII tables are padded with blanks, everything else with zeros, cf. FITSv4, section 3.3.2
+    let fill = if hdu.header().extension() == Some(ExtensionType::AsciiTable) {
+        b' '
+    } else {
+        0
+    };
+    let padding = (2880 - data.len() % 2880) % 2880;
+    data.resize(data.len() + padding, fill);
+
+    Ok(data)
+}
@@ -0,0 +1,70 @@
+use fits_io::hdu::HDU;
+use fits_io::{Fits, FitsSlice};
+
+fn header(records: &[&str]) -> Vec<u8> {
+    let mut bytes = vec![b' '; 2880];
+    for (index, record) in records.iter().enumerate() {
+        bytes[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+    bytes
+}
+
+#[test]
+pub fn unchanged_cards_should_keep_their_bytes()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let data = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "EXPTIME = 300.                   /exposure in seconds",
+        "MYKEY   = 'unknown'",
+        "DATE-OBS= '17/11/2025'",
+        "OBJECT  = 'M31'",
+        "HISTORY   indented history",
+        "END",
+    ]);
+
+    let mut fits = FitsSlice::from_slice(&data)?;
+    let header = fits.primary_hdu_mut().header_mut();
+    assert_eq!(
+        header.original_record("EXPTIME"),
+        Some(&data[3 * 80..4 * 80])
+    );
+    assert!(!header.is_modified("EXPTIME"));
+
+    header.set_object("M33");
+    assert!(header.is_modified("OBJECT"));
+    assert!(!header.is_modified("MYKEY"));
+    assert_eq!(
+        header.original_record("OBJECT"),
+        Some(&data[6 * 80..7 * 80])
+    );
+
+    let written = header.to_bytes();
+    let object = 6 * 80..7 * 80;
+    assert_eq!(written[..object.start], data[..object.start]);
+    assert_eq!(written[object.end..], data[object.end..]);
+    assert!(written[object].starts_with(b"OBJECT  = 'M33     '"));
+
+    Ok(())
+}
+
+#[test]
+pub fn setting_the_same_value_should_not_modify_a_card()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let data = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "OBJECT  =                'M31'",
+        "END",
+    ]);
+
+    let mut fits = FitsSlice::from_slice(&data)?;
+    fits.primary_hdu_mut().header_mut().set_object("M31");
+
+    assert!(!fits.primary_hdu().header().is_modified("OBJECT"));
+    assert_eq!(fits.to_vec()?, data);
+
+    Ok(())
+}
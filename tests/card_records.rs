@@ -0,0 +1,92 @@
+use fits_io::header::{Card, Value};
+
+fn record(text: &str) -> [u8; 80] {
+    let mut record = [b' '; 80];
+    record[..text.len()].copy_from_slice(text.as_bytes());
+    record
+}
+
+#[test]
+pub fn to_record_should_follow_fixed_format() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let card = Card::try_from(&record("NAXIS1  = 6000 / length of data axis 1"))?;
+    assert_eq!(
+        card.to_record(),
+        record("NAXIS1  =                 6000 / length of data axis 1")
+    );
+
+    let card = Card::try_from(&record("OBJECT  = 'M31'"))?;
+    assert_eq!(card.to_record(), record("OBJECT  = 'M31     '"));
+
+    Ok(())
+}
+
+#[test]
+pub fn to_record_should_escape_quotes() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let card = Card::try_from(&record("OBSERVER= 'O''HARA' / it's a name / with a slash"))?;
+    assert_eq!(
+        Value::from(&card),
+        Value::String {
+            value: "O'HARA".into(),
+            comment: Some("it's a name / with a slash".into()),
+        }
+    );
+    assert_eq!(
+        card.to_record(),
+        record("OBSERVER= 'O''HARA ' / it's a name / with a slash")
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn to_record_should_truncate_long_values()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let card = Card::Object {
+        value: "'".repeat(40),
+        comment: Some("dropped".into()),
+    };
+    let record = card.to_record();
+    let parsed = Card::try_from(&record)?;
+
+    assert_eq!(&record[..11], b"OBJECT  = '");
+    assert_eq!(
+        Value::from(&parsed),
+        Value::String {
+            value: "'".repeat(34),
+            comment: None,
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn complex_values_should_round_trip() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let card = Card::try_from(&record("CPLXINT = (3, -4) / complex integer"))?;
+    assert_eq!(
+        Value::from(&card),
+        Value::ComplexInteger {
+            real: 3,
+            imaginary: -4,
+            comment: Some("complex integer".into())
+        }
+    );
+    assert_eq!(
+        card.to_record(),
+        record("CPLXINT =                   (3,                 -4) / complex integer")
+    );
+
+    let card = Card::try_from(&record("CPLXFLT = (1.5D2, -2.25E-1)"))?;
+    assert_eq!(
+        Value::from(&card),
+        Value::ComplexFloat {
+            real: 150.0,
+            imaginary: -0.225,
+            comment: None
+        }
+    );
+    assert_eq!(Card::try_from(&card.to_record())?, card);
+
+    Ok(())
+}
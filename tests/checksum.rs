@@ -0,0 +1,70 @@
+use fits_io::fs::FsFits;
+use fits_io::hdu::{ChecksumStatus, ChecksumVerification, HDU, ImageHDU};
+use fits_io::{Fits, FitsSlice};
+
+#[test]
+pub fn saved_files_should_have_valid_checksums()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::temp_dir().join("fits-io-checksum.fits");
+    let pixels: Vec<i16> = (0..8 * 8).map(|i| i * 37 - 500).collect();
+
+    let mut fits = FsFits::new(&path);
+    fits.primary_hdu_mut()
+        .set_raw_images_i16(8, 8, &[&pixels])?;
+    fits.save()?;
+    assert!(fits.primary_hdu().verify_checksum()?.is_valid());
+
+    let written = std::fs::read(&path)?;
+    let fits = FsFits::open(&path)?;
+    assert!(fits.primary_hdu().verify_checksum()?.is_valid());
+    assert_eq!(fits.to_vec()?, written);
+    std::fs::remove_file(&path)?;
+
+    // Corrupt a single pixel
+    let mut corrupted = written.clone();
+    corrupted[2880 + 10] ^= 0x01;
+    let fits = FitsSlice::from_slice(&corrupted)?;
+    assert_eq!(
+        fits.primary_hdu().verify_checksum()?,
+        ChecksumVerification {
+            header: ChecksumStatus::Invalid,
+            data: ChecksumStatus::Invalid,
+        }
+    );
+
+    // Writing a changed HDU brings the checksums up to date again
+    let rewritten = fits.to_vec()?;
+    let fits = FitsSlice::from_slice(&rewritten)?;
+    assert!(fits.primary_hdu().verify_checksum()?.is_valid());
+
+    Ok(())
+}
+
+#[test]
+pub fn files_without_checksums_should_be_reported_missing()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut data = vec![b' '; 2880];
+    for (index, record) in [
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "END",
+    ]
+    .iter()
+    .enumerate()
+    {
+        data[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+
+    let fits = FitsSlice::from_slice(&data)?;
+    assert_eq!(
+        fits.primary_hdu().verify_checksum()?,
+        ChecksumVerification {
+            header: ChecksumStatus::Missing,
+            data: ChecksumStatus::Missing,
+        }
+    );
+    assert_eq!(fits.to_vec()?, data);
+
+    Ok(())
+}
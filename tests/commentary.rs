@@ -0,0 +1,63 @@
+use fits_io::hdu::HDU;
+use fits_io::{Fits, FitsSlice};
+
+fn empty_file() -> Vec<u8> {
+    let mut bytes = vec![b' '; 2880];
+    for (index, record) in [
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "END",
+    ]
+    .iter()
+    .enumerate()
+    {
+        bytes[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+    bytes
+}
+
+#[test]
+pub fn history_should_be_wrapped_and_round_trip()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FitsSlice::from_slice(&empty_file())?;
+    let header = fits.primary_hdu_mut().header_mut();
+
+    let long = "calibrated with master dark /data/calibration/darks/2025-11-17/master_dark_600s_gain100_-10C.fits and master flat";
+    header.add_history("calibrated with master dark X");
+    header.add_history(long);
+    header.add_comment("taken in twilight");
+
+    let history: Vec<String> = header.history().map(String::from).collect();
+    assert_eq!(
+        history,
+        [
+            "calibrated with master dark X",
+            "calibrated with master dark",
+            "/data/calibration/darks/2025-11-17/master_dark_600s_gain100_-10C.fits",
+            "and master flat",
+        ]
+    );
+    assert!(history.iter().all(|line| line.len() <= 72));
+    assert_eq!(header.comments().collect::<Vec<_>>(), ["taken in twilight"]);
+
+    let fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    let header = fits.primary_hdu().header();
+    assert_eq!(header.history().collect::<Vec<_>>(), history);
+    assert_eq!(header.comments().count(), 1);
+
+    Ok(())
+}
+
+#[test]
+pub fn words_longer_than_a_card_should_be_split()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FitsSlice::from_slice(&empty_file())?;
+    let header = fits.primary_hdu_mut().header_mut();
+
+    header.add_comment(&"x".repeat(100));
+    let comments: Vec<_> = header.comments().map(str::len).collect();
+    assert_eq!(comments, [72, 28]);
+
+    Ok(())
+}
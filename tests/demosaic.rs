@@ -0,0 +1,142 @@
+use fits_io::hdu::ImageHDU;
+use fits_io::header::BayerPattern;
+use fits_io::image::DemosaicAlgorithm;
+use fits_io::image::demosaic::{binned_superpixel, demosaic};
+use fits_io::{Fits, FitsSlice};
+use image::{ImageBuffer, Luma};
+
+const PATTERNS: [BayerPattern; 4] = [
+    BayerPattern::RGGB,
+    BayerPattern::BGGR,
+    BayerPattern::GRBG,
+    BayerPattern::GBRG,
+];
+
+const ALGORITHMS: [DemosaicAlgorithm; 4] = [
+    DemosaicAlgorithm::Superpixel,
+    DemosaicAlgorithm::Bilinear,
+    DemosaicAlgorithm::Vng,
+    DemosaicAlgorithm::Ahd,
+];
+
+/// The filter colors of the 2x2 cell, as indices into an rgb color
+fn cell(pattern: BayerPattern) -> [[usize; 2]; 2] {
+    match pattern {
+        BayerPattern::RGGB => [[0, 1], [1, 2]],
+        BayerPattern::BGGR => [[2, 1], [1, 0]],
+        BayerPattern::GRBG => [[1, 0], [2, 1]],
+        BayerPattern::GBRG => [[1, 2], [0, 1]],
+    }
+}
+
+/// A mosaic of a single color, as seen through the given filter pattern
+fn mosaic(pattern: BayerPattern, width: u32, height: u32, rgb: [f64; 3]) -> Vec<f64> {
+    (0..width * height)
+        .map(|i| rgb[cell(pattern)[(i / width % 2) as usize][(i % width % 2) as usize]])
+        .collect()
+}
+
+#[test]
+pub fn every_algorithm_should_handle_every_pattern() {
+    let rgb = [0.8, 0.5, 0.2];
+    for pattern in PATTERNS {
+        for (width, height) in [(6, 4), (5, 3)] {
+            let data = mosaic(pattern, width, height, rgb);
+            let f64_mosaic =
+                ImageBuffer::<Luma<f64>, _>::from_raw(width, height, data.clone()).unwrap();
+            let f32_mosaic = ImageBuffer::<Luma<f32>, _>::from_raw(
+                width,
+                height,
+                data.iter().map(|value| *value as f32).collect(),
+            )
+            .unwrap();
+
+            for algorithm in ALGORITHMS {
+                let color = demosaic(&f64_mosaic, pattern, algorithm).unwrap();
+                assert_eq!(color.dimensions(), (width, height));
+                for pixel in color.pixels() {
+                    for (value, expected) in pixel.0.iter().zip(rgb) {
+                        assert!(
+                            (value - expected).abs() < 1e-9,
+                            "{:?} {:?}: {:?}",
+                            pattern,
+                            algorithm,
+                            pixel
+                        );
+                    }
+                }
+
+                let color = demosaic(&f32_mosaic, pattern, algorithm).unwrap();
+                assert!(color.pixels().all(|pixel| (pixel[0] - 0.8).abs() < 1e-6
+                    && (pixel[2] - 0.2).abs() < 1e-6));
+            }
+
+            let binned = binned_superpixel(&f64_mosaic, pattern).unwrap();
+            assert_eq!(binned.dimensions(), (width / 2, height / 2));
+            assert!(binned.pixels().all(|pixel| pixel.0 == rgb));
+        }
+    }
+}
+
+#[test]
+pub fn interpolation_overshoots_should_saturate() {
+    // Sharp edges make the color difference based algorithms overshoot the range of the type
+    let data = (0..64_u32)
+        .map(|i| {
+            if (i % 8) / 3 == 1 || i / 24 == 1 {
+                255
+            } else {
+                i as u8 % 7
+            }
+        })
+        .collect::<Vec<_>>();
+    let u8_mosaic = ImageBuffer::<Luma<u8>, _>::from_raw(8, 8, data.clone()).unwrap();
+    let f64_mosaic = ImageBuffer::<Luma<f64>, _>::from_raw(
+        8,
+        8,
+        data.iter().map(|value| *value as f64).collect(),
+    )
+    .unwrap();
+
+    let mut overshoots = 0;
+    for algorithm in ALGORITHMS {
+        let expected = demosaic(&f64_mosaic, BayerPattern::RGGB, algorithm).unwrap();
+        let color = demosaic(&u8_mosaic, BayerPattern::RGGB, algorithm).unwrap();
+        for (value, expected) in color.iter().zip(expected.iter()) {
+            overshoots += usize::from(*expected > 255.0);
+            assert_eq!(*value, expected.clamp(0.0, 255.0) as u8, "{:?}", algorithm);
+        }
+    }
+    assert!(overshoots > 0);
+}
+
+#[test]
+pub fn mosaics_smaller_than_a_filter_cell_should_be_rejected() {
+    for (width, height) in [(1, 4), (4, 1), (1, 1)] {
+        let mosaic = ImageBuffer::<Luma<u16>, _>::new(width, height);
+        for algorithm in ALGORITHMS {
+            assert!(demosaic(&mosaic, BayerPattern::RGGB, algorithm).is_err());
+        }
+    }
+}
+
+#[test]
+pub fn rgb_image_should_use_the_chosen_algorithm()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FitsSlice::from_template("BAYERPAT = 'GBRG'")?;
+    let data = mosaic(BayerPattern::GBRG, 4, 4, [200.0, 100.0, 0.0]);
+    let data = data.iter().map(|value| *value as u16).collect::<Vec<_>>();
+    fits.primary_hdu_mut().set_raw_images_u16(4, 4, &[&data])?;
+    let image = fits.primary_hdu().read_image(0)?.unwrap();
+
+    let superpixel = image.normalized_superpixel()?;
+    assert_eq!(superpixel.dimensions(), (2, 2));
+    assert!(superpixel.pixels().all(|pixel| pixel.0 == [1.0, 0.5, 0.0]));
+
+    for algorithm in ALGORITHMS {
+        let rgb = image.rgb_image(algorithm)?;
+        assert_eq!(rgb.dimensions(), (4, 4));
+        assert!(rgb.pixels().all(|pixel| pixel.0 == [255, 127, 0]));
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use fits_io::header::{Bitpix, Card};
+use fits_io::{Error, FitsSlice};
+
+fn header(records: &[&str]) -> Vec<u8> {
+    let mut bytes = vec![b' '; 2880];
+    for (index, record) in records.iter().enumerate() {
+        bytes[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+    bytes
+}
+
+#[test]
+pub fn missing_mandatory_keyword_should_name_the_keyword() {
+    let data = header(&[
+        "SIMPLE  =                    T",
+        "NAXIS   =                    0",
+        "END",
+    ]);
+
+    match FitsSlice::from_slice(&data).unwrap_err() {
+        Error::MissingKeyword { hdu, keyword } => {
+            assert_eq!(hdu, 0);
+            assert_eq!(keyword, "BITPIX");
+        }
+        err => panic!("Unexpected error: {}", err),
+    }
+}
+
+#[test]
+pub fn short_data_unit_should_be_truncated_data() {
+    let data = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                   16",
+        "NAXIS   =                    2",
+        "NAXIS1  =                   10",
+        "NAXIS2  =                   10",
+        "END",
+    ]);
+
+    match FitsSlice::from_slice(&data).unwrap_err() {
+        Error::TruncatedData { expected, actual } => {
+            assert_eq!(expected, 200);
+            assert_eq!(actual, 0);
+        }
+        err => panic!("Unexpected error: {}", err),
+    }
+}
+
+#[test]
+pub fn unknown_bitpix_should_be_unsupported() {
+    assert!(matches!(
+        Bitpix::try_from(24),
+        Err(Error::UnsupportedBitpix(24))
+    ));
+}
+
+#[test]
+pub fn bad_value_should_name_the_keyword() {
+    let mut record = [b' '; 80];
+    record[..30].copy_from_slice(b"EXPTIME =                 'ab'");
+
+    match Card::try_from(&record).unwrap_err() {
+        Error::InvalidValue { keyword, .. } => assert_eq!(keyword, "EXPTIME"),
+        err => panic!("Unexpected error: {}", err),
+    }
+}
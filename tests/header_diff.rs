@@ -0,0 +1,89 @@
+use fits_io::hdu::HDU;
+use fits_io::header::{DiffOptions, Header, KeywordDiff};
+use fits_io::{Fits, FitsSlice};
+
+fn header(records: &[&str]) -> Result<Header, Box<dyn std::error::Error + Send + Sync>> {
+    let mut bytes = vec![b' '; 2880];
+    for (index, record) in records.iter().enumerate() {
+        bytes[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+    Ok(FitsSlice::from_slice(&bytes)?
+        .primary_hdu()
+        .header()
+        .clone())
+}
+
+#[test]
+pub fn diff_should_report_added_removed_and_changed_keywords()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let old = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "OBJECT  = 'M31     '",
+        "CCD-TEMP=                -10.0 / sensor temperature",
+        "FILTER  = 'Ha      '",
+        "END",
+    ])?;
+    let new = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "OBJECT  = 'M31     '",
+        "CCD-TEMP=                -10.0 / sensor temp",
+        "GAIN    =                  100",
+        "END",
+    ])?;
+
+    let differences = old.diff(&new);
+    assert_eq!(differences.len(), 3);
+    assert!(matches!(
+        &differences[0],
+        KeywordDiff::Changed { old, new }
+            if old.comment().as_deref() == Some("sensor temperature")
+                && new.comment().as_deref() == Some("sensor temp")
+    ));
+    assert!(matches!(&differences[1], KeywordDiff::Removed(card) if card.key() == "FILTER"));
+    assert!(matches!(&differences[2], KeywordDiff::Added(card) if card.key() == "GAIN"));
+
+    assert!(old.diff(&old).is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn diff_should_ignore_keywords_and_small_differences()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let old = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "DATE    = '2025-11-17T19:01:34'",
+        "CRVAL1  =         83.822083333",
+        "HISTORY   bias subtracted",
+        "END",
+    ])?;
+    let new = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "DATE    = '2025-11-18T08:00:00'",
+        "CRVAL1  =         83.822083334",
+        "HISTORY   bias subtracted",
+        "HISTORY   dark subtracted",
+        "END",
+    ])?;
+
+    let keys: Vec<_> = old.diff(&new).iter().map(KeywordDiff::key).collect();
+    assert_eq!(keys, ["DATE", "CRVAL1", "HISTORY"]);
+
+    let differences = old.diff_with(
+        &new,
+        DiffOptions::new()
+            .ignore(&["DATE", "CHECKSUM", "HISTORY"])
+            .tolerance(1e-6),
+    );
+    assert!(differences.is_empty());
+
+    Ok(())
+}
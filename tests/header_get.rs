@@ -0,0 +1,62 @@
+use chrono::{TimeZone, Utc};
+use fits_io::hdu::HDU;
+use fits_io::header::{BayerPattern, ImageType};
+use fits_io::{Fits, FitsSlice};
+use std::time::Duration;
+
+fn header(records: &[&str]) -> Vec<u8> {
+    let mut bytes = vec![b' '; 2880];
+    for (index, record) in records.iter().enumerate() {
+        bytes[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+    bytes
+}
+
+#[test]
+pub fn get_should_convert_values() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let data = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "EXPTIME =                300.0",
+        "DATE-OBS= '2025-11-17T19:01:34.5'",
+        "IMAGETYP= 'Light Frame'",
+        "BAYERPAT= 'RGGB'",
+        "GAIN    =                  100",
+        "SWCREATE= 'N.I.N.A.'",
+        "COOLED  =                    T",
+        "END",
+    ]);
+    let fits = FitsSlice::from_slice(&data)?;
+    let header = fits.primary_hdu().header();
+
+    assert_eq!(header.get::<i64>("GAIN")?, Some(100));
+    assert_eq!(header.get::<f64>("GAIN")?, Some(100.0));
+    assert_eq!(header.get::<String>("SWCREATE")?, Some("N.I.N.A.".into()));
+    assert_eq!(header.get::<bool>("COOLED")?, Some(true));
+    assert_eq!(
+        header.get::<Duration>("EXPTIME")?,
+        Some(Duration::from_secs(300))
+    );
+    assert_eq!(
+        header.get("DATE-OBS")?,
+        Some(
+            Utc.with_ymd_and_hms(2025, 11, 17, 19, 1, 34).unwrap()
+                + chrono::Duration::milliseconds(500)
+        )
+    );
+    assert_eq!(
+        header.get::<ImageType>("IMAGETYP")?,
+        Some(ImageType::Unknown("Light Frame".into()))
+    );
+    assert_eq!(header.get("BAYERPAT")?, Some(BayerPattern::RGGB));
+    assert_eq!(header.get::<i64>("MISSING")?, None);
+
+    let err = header.get::<i64>("SWCREATE").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid value for SWCREATE: expected an integer, found a string 'N.I.N.A.'"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use fits_io::fs::FsFits;
+use fits_io::hdu::HDU;
+use fits_io::header::{Card, Value};
+use fits_io::{Fits, FitsSlice};
+use std::time::Duration;
+
+#[test]
+pub fn setters_should_update_header() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FsFits::new(&std::env::temp_dir().join("fits-io-setters.fits"));
+    let header = fits.primary_hdu_mut().header_mut();
+
+    header.set_object("M31");
+    header.set_exposure_time(Duration::from_secs(120));
+    header.set_ccd_temperature(-10.0);
+    header.set("FOCUSPOS", 12000, Some("focuser position"))?;
+    header.set("OBSNOTE", "clear sky", None)?;
+    header.set_object("M33");
+
+    assert_eq!(header.object(), Some("M33"));
+    assert_eq!(header.exposure_time(), Some(Duration::from_secs(120)));
+    assert_eq!(header.ccd_temperature(), Some(-10.0));
+    assert_eq!(header.focus_position(), Some(12000));
+    assert_eq!(
+        header.raw_card("OBSNOTE"),
+        vec![Value::String {
+            value: "clear sky".into(),
+            comment: None
+        }]
+    );
+
+    let written = fits.to_vec()?;
+    let fits = FitsSlice::from_slice(&written)?;
+    assert_eq!(fits.primary_hdu().header().object(), Some("M33"));
+    assert_eq!(
+        fits.primary_hdu().header().raw_card("FOCUSPOS")[0].comment_to_string(),
+        "focuser position"
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn mutations_should_keep_mandatory_order()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FsFits::new(&std::env::temp_dir().join("fits-io-mandatory-order.fits"));
+    let header = fits.primary_hdu_mut().header_mut();
+
+    assert!(header.set("NAXIS", 2, None).is_err());
+    assert!(header.set("NAXIS1", 2, None).is_err());
+    assert!(header.remove("BITPIX").is_err());
+    assert!(header.set("lower", 2, None).is_err());
+
+    let card = Card::Object {
+        value: "M31".into(),
+        comment: None,
+    };
+    assert!(header.insert_after("SIMPLE", card.clone()).is_err());
+    assert!(header.insert_after("END", card.clone()).is_err());
+    header.insert_after("NAXIS", card)?;
+    assert_eq!(header.object(), Some("M31"));
+
+    assert!(header.remove("OBJECT")?);
+    assert!(!header.remove("OBJECT")?);
+    assert_eq!(header.object(), None);
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use fits_io::hdu::HDU;
+use fits_io::header::Value;
+use fits_io::{Fits, FitsSlice};
+
+fn header(records: &[&str]) -> Vec<u8> {
+    let mut bytes = vec![b' '; 2880];
+    for (index, record) in records.iter().enumerate() {
+        bytes[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+    bytes
+}
+
+fn table_like_file() -> Vec<u8> {
+    header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "TTYPE1  = 'RA      '           / right ascension",
+        "TTYPE2  = 'DEC     '",
+        "TTYPE12 = 'MAG     '",
+        "TUNIT1  = 'deg     '",
+        "INSTRUME= 'ZWO ASI2600MM'",
+        "COMMENT   taken in twilight",
+        "END",
+    ])
+}
+
+#[test]
+pub fn find_should_match_wildcards() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let fits = FitsSlice::from_slice(&table_like_file())?;
+    let header = fits.primary_hdu().header();
+
+    let keys = |pattern| {
+        header
+            .find(pattern)
+            .map(|card| card.key())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys("TTYPE*"), ["TTYPE1", "TTYPE2", "TTYPE12"]);
+    assert_eq!(keys("TTYPE#"), ["TTYPE1", "TTYPE2", "TTYPE12"]);
+    assert_eq!(keys("T????1"), ["TTYPE1", "TUNIT1"]);
+    assert_eq!(keys("ttype2"), ["TTYPE2"]);
+    assert!(keys("TTYPE").is_empty());
+
+    let instrument: Vec<_> = header
+        .find_matching(|key| key.starts_with("INST"))
+        .map(|card| card.value())
+        .collect();
+    assert_eq!(
+        instrument,
+        [Value::String {
+            value: "ZWO ASI2600MM".into(),
+            comment: None,
+        }]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn select_should_apply_include_and_exclude_lists()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let fits = FitsSlice::from_slice(&table_like_file())?;
+    let header = fits.primary_hdu().header();
+
+    let keys: Vec<_> = header
+        .select(&["T*", "INSTRUME"], &["TTYPE1#"])
+        .map(|card| card.key())
+        .collect();
+    assert_eq!(keys, ["TTYPE1", "TTYPE2", "TUNIT1", "INSTRUME"]);
+
+    Ok(())
+}
+
+#[test]
+pub fn iter_should_list_cards_in_order() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let fits = FitsSlice::from_slice(&table_like_file())?;
+    let header = fits.primary_hdu().header();
+
+    let cards: Vec<_> = header
+        .iter()
+        .map(|card| (card.key(), card.value().value_to_string(), card.comment()))
+        .collect();
+    assert_eq!(cards.len(), 9);
+    assert_eq!(
+        cards[3],
+        ("TTYPE1".into(), "RA".into(), Some("right ascension".into()))
+    );
+    assert_eq!(
+        cards[8],
+        (
+            "COMMENT".into(),
+            "".into(),
+            Some("taken in twilight".into())
+        )
+    );
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use fits_io::Error;
+use fits_io::header::Header;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const HEADER: &str = "SIMPLE  =                    T
+BITPIX  =                   16
+NAXIS   =                    0
+DATE-OBS= '2025-11-17T19:01:34'
+EXPTIME =                 30.0 / exposure in seconds
+CCD-TEMP=                -10.5
+GAIN    =                  100
+TARGET  =
+IMAGETYP= 'Light Frame'
+";
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ObservationMeta {
+    #[serde(rename = "DATE-OBS")]
+    date_obs: String,
+    exptime: f64,
+    #[serde(rename = "CCD-TEMP")]
+    ccd_temp: Option<f32>,
+    gain: u32,
+    target: Option<String>,
+    filter: Option<String>,
+}
+
+#[test]
+pub fn header_should_deserialize_into_a_struct()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let header = Header::from_text(HEADER)?;
+
+    let meta: ObservationMeta = header.deserialize()?;
+    assert_eq!(
+        meta,
+        ObservationMeta {
+            date_obs: "2025-11-17T19:01:34+00:00".into(),
+            exptime: 30.0,
+            ccd_temp: Some(-10.5),
+            gain: 100,
+            target: None,
+            filter: None,
+        }
+    );
+
+    assert!(matches!(
+        header.deserialize::<BTreeMap<String, i64>>(),
+        Err(Error::InvalidValue { keyword, .. }) if keyword == "SIMPLE"
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn struct_should_serialize_into_cards() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let mut header = Header::from_text(HEADER)?;
+    let meta = ObservationMeta {
+        date_obs: "2025-11-18T20:00:00+00:00".into(),
+        exptime: 60.0,
+        ccd_temp: None,
+        gain: 120,
+        target: Some("M42".into()),
+        filter: Some("Ha".into()),
+    };
+
+    header.serialize(&meta)?;
+    assert_eq!(header.get::<i64>("GAIN")?, Some(120));
+    assert_eq!(header.get::<String>("FILTER")?.as_deref(), Some("Ha"));
+    assert_eq!(
+        header
+            .iter()
+            .find(|card| card.key() == "EXPTIME")
+            .and_then(|card| card.comment())
+            .as_deref(),
+        Some("exposure in seconds")
+    );
+    assert_eq!(header.get::<f64>("CCD-TEMP")?, Some(-10.5));
+
+    let round_trip: ObservationMeta = Header::from_text(&header.to_text())?.deserialize()?;
+    assert_eq!(
+        round_trip,
+        ObservationMeta {
+            ccd_temp: Some(-10.5),
+            ..meta
+        }
+    );
+
+    #[derive(Serialize)]
+    struct Layout {
+        naxis: i64,
+    }
+    assert!(matches!(
+        header.serialize(&Layout { naxis: 2 }),
+        Err(Error::InvalidKeyword { keyword, .. }) if keyword == "NAXIS"
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use fits_io::hdu::HDU;
+use fits_io::header::Header;
+use fits_io::{Error, Fits, FitsSlice};
+
+const SCAMP_HEAD: &str = "COMMENT   Astrometric solution by SCAMP
+EQUINOX =        2000.00000000 / Mean equinox
+RADESYS = 'ICRS    '           / Astrometric system
+CTYPE1  = 'RA---TAN'           / WCS projection type for this axis
+CRVAL1  =   8.382208333000E+01 / World coordinate on this axis
+CRPIX1  =   3.121500000000E+03 / Reference pixel on this axis
+CD1_1   =  -2.061280000000E-04 / Linear projection matrix
+FLXSCALE=   1.000000000000E+00 / SCAMP relative flux scale
+END
+";
+
+#[test]
+pub fn text_should_round_trip() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let header = Header::from_text(SCAMP_HEAD)?;
+
+    assert_eq!(header.get::<f64>("CRVAL1")?, Some(83.82208333));
+    assert_eq!(header.get::<String>("CTYPE1")?.as_deref(), Some("RA---TAN"));
+    assert_eq!(
+        header.comments().collect::<Vec<_>>(),
+        ["Astrometric solution by SCAMP"]
+    );
+    assert_eq!(header.to_text(), SCAMP_HEAD);
+
+    let without_end = SCAMP_HEAD.replace("END\n", "");
+    assert_eq!(Header::from_text(&without_end)?.to_text(), SCAMP_HEAD);
+
+    Ok(())
+}
+
+#[test]
+pub fn lines_longer_than_a_card_should_be_rejected() {
+    let text = format!("OBJECT  = '{}'", "x".repeat(80));
+    assert!(matches!(
+        Header::from_text(&text),
+        Err(Error::InvalidCard { keyword, .. }) if keyword == "OBJECT"
+    ));
+}
+
+#[test]
+pub fn merge_should_apply_a_wcs_solution() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut bytes = vec![b' '; 2880];
+    for (index, record) in [
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "CRVAL1  =                 83.8",
+        "END",
+    ]
+    .iter()
+    .enumerate()
+    {
+        bytes[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+    let mut fits = FitsSlice::from_slice(&bytes)?;
+
+    let solution = Header::from_text(&format!("BITPIX  =                   16\n{}", SCAMP_HEAD))?;
+    fits.primary_hdu_mut().header_mut().merge(&solution);
+
+    let header = fits.primary_hdu().header();
+    assert_eq!(header.get::<f64>("CRVAL1")?, Some(83.82208333));
+    assert_eq!(header.get::<f64>("CD1_1")?, Some(-2.06128e-4));
+    assert_eq!(header.bitpix(), fits_io::header::Bitpix::U8);
+    assert_eq!(
+        header.iter().filter(|card| card.key() == "CRVAL1").count(),
+        1
+    );
+    assert_eq!(header.comments().count(), 1);
+
+    let fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    assert_eq!(
+        fits.primary_hdu()
+            .header()
+            .get::<String>("RADESYS")?
+            .as_deref(),
+        Some("ICRS")
+    );
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use fits_io::fs::FsFits;
+use fits_io::hdu::HDU;
+use fits_io::header::{Card, Value};
+use fits_io::{Fits, FitsSlice};
+
+fn record(text: &str) -> [u8; 80] {
+    let mut record = [b' '; 80];
+    record[..text.len()].copy_from_slice(text.as_bytes());
+    record
+}
+
+#[test]
+pub fn hierarch_cards_should_round_trip() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let text = "HIERARCH ESO DET CHIP TEMP = -120.5 / chip temperature";
+    let card = Card::try_from(&record(text))?;
+    assert_eq!(card.key(), "HIERARCH ESO DET CHIP TEMP");
+    assert_eq!(
+        Value::from(&card),
+        Value::Float {
+            value: -120.5,
+            comment: Some("chip temperature".into())
+        }
+    );
+    assert_eq!(card.to_record(), record(text));
+
+    Ok(())
+}
+
+#[test]
+pub fn hierarch_should_be_found_by_full_name()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FsFits::new(&std::env::temp_dir().join("fits-io-hierarch.fits"));
+    let header = fits.primary_hdu_mut().header_mut();
+    header.set_hierarch("ESO.DET.CHIP.TEMP", -120.5, Some("chip temperature"))?;
+    header.set_hierarch("ESO OBS NAME", "M31 mosaic", None)?;
+    header.set_hierarch("HIERARCH ESO DET CHIP TEMP", -119.0, None)?;
+
+    let written = fits.to_vec()?;
+    let fits = FitsSlice::from_slice(&written)?;
+    let header = fits.primary_hdu().header();
+
+    assert_eq!(
+        header.hierarch("ESO DET CHIP TEMP"),
+        Some(&Value::Float {
+            value: -119.0,
+            comment: Some("chip temperature".into())
+        })
+    );
+    assert_eq!(
+        header.hierarch("HIERARCH ESO.OBS.NAME"),
+        Some(&Value::String {
+            value: "M31 mosaic".into(),
+            comment: None
+        })
+    );
+    assert_eq!(header.hierarch("ESO DET CHIP"), None);
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use fits_io::hdu::ImageHDU;
+use fits_io::image::{Image, Normalization};
+use fits_io::{Fits, FitsSlice};
+
+fn scaled_image(
+    template: &str,
+    pixels: &[i16],
+) -> Result<Image, Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FitsSlice::from_template(template)?;
+    fits.primary_hdu_mut().set_raw_images_i16(2, 2, &[pixels])?;
+    let fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    Ok(fits.primary_hdu().read_image(0)?.unwrap())
+}
+
+#[test]
+pub fn physical_should_apply_bscale_and_bzero()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let image = scaled_image("BSCALE = 0.5\nBZERO = 100.0", &[-200, 0, 100, 200])?;
+
+    assert_eq!(image.physical().into_raw(), [0.0, 100.0, 150.0, 200.0]);
+    assert_eq!(image.data_range(), Some((0.0, 200.0)));
+    assert_eq!(image.normalized().into_raw(), [0.0, 0.5, 0.75, 1.0]);
+
+    Ok(())
+}
+
+#[test]
+pub fn normalization_should_use_the_chosen_range()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let image = scaled_image("DATAMIN = 0.0\nDATAMAX = 1000.0", &[0, 250, 500, 2000])?;
+
+    assert_eq!(image.normalized().into_raw(), [0.0, 0.25, 0.5, 1.0]);
+    assert_eq!(
+        image.normalized_with(Normalization::DataRange).into_raw(),
+        [0.0, 0.125, 0.25, 1.0]
+    );
+    assert_eq!(
+        image
+            .normalized_with(Normalization::Range {
+                min: 250.0,
+                max: 750.0
+            })
+            .into_raw(),
+        [0.0, 0.0, 0.5, 1.0]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use fits_io::fs::FsFits;
+use fits_io::hdu::HDU;
+use fits_io::header::Value;
+use fits_io::{Fits, FitsSlice};
+
+fn header(records: &[&str]) -> Vec<u8> {
+    let mut bytes = vec![b' '; 2880];
+    for (index, record) in records.iter().enumerate() {
+        bytes[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+    bytes
+}
+
+#[test]
+pub fn continued_strings_should_be_joined() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let data = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "LONGSTRN= 'OGIP 1.0'",
+        "OBJECT  = 'Andromeda galaxy, &' / first part",
+        "CONTINUE  'also known as M31&'",
+        "CONTINUE  ' or NGC 224' / last part",
+        "END",
+    ]);
+
+    let fits = FitsSlice::from_slice(&data)?;
+    let header = fits.primary_hdu().header();
+    assert_eq!(
+        header.object(),
+        Some("Andromeda galaxy, also known as M31 or NGC 224")
+    );
+    assert_eq!(
+        header.raw_card("OBJECT")[0].comment_to_string(),
+        "first part last part"
+    );
+    assert_eq!(fits.to_vec()?, data);
+
+    Ok(())
+}
+
+#[test]
+pub fn long_strings_should_be_continued() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let text = "It's a long observation note, ".repeat(6);
+
+    let mut fits = FsFits::new(&std::env::temp_dir().join("fits-io-long-strings.fits"));
+    let header = fits.primary_hdu_mut().header_mut();
+    header.set("NOTE", text.as_str(), Some("observer notes"))?;
+
+    let written = fits.to_vec()?;
+    let records: Vec<&[u8]> = written.chunks(80).collect();
+    assert!(records[3].starts_with(b"LONGSTRN= 'OGIP 1.0'"));
+    assert!(records[4].starts_with(b"NOTE    = 'It''s a long"));
+    assert!(records[5].starts_with(b"CONTINUE  '"));
+
+    let fits = FitsSlice::from_slice(&written)?;
+    assert_eq!(
+        fits.primary_hdu().header().raw_card("NOTE"),
+        vec![Value::String {
+            value: text.trim_end().into(),
+            comment: Some("observer notes".into())
+        }]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn comments_should_survive_a_full_last_chunk()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let long_comment = "a comment that is far too long to share a record with any part of the value, \
+                        so it has to be split over several records";
+    for comment in ["observer notes", long_comment] {
+        for len in 150..=300 {
+            let text = "x".repeat(len);
+            let mut fits = FsFits::new(&std::env::temp_dir().join("fits-io-long-comments.fits"));
+            let header = fits.primary_hdu_mut().header_mut();
+            header.set("NOTE", text.as_str(), Some(comment))?;
+
+            let fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+            assert_eq!(
+                fits.primary_hdu().header().raw_card("NOTE"),
+                vec![Value::String {
+                    value: text,
+                    comment: Some(comment.into())
+                }],
+                "{} characters",
+                len
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use fits_io::fs::FsFits;
+use fits_io::hdu::{ExtensionHDU, HDU, ImageHDU};
+use fits_io::image::Image;
+use fits_io::{Error, Fits};
+
+#[test]
+pub fn open_mmap_should_view_pixels_in_place()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::temp_dir().join("fits-io-open-mmap.fits");
+    let first: Vec<u16> = (0..12).map(|i| i * 5000).collect();
+    let second: Vec<u16> = (0..12).map(|i| 65535 - i).collect();
+    let mut fits = FsFits::new(&path);
+    fits.primary_hdu_mut()
+        .set_raw_images_u16(4, 3, &[&first, &second])?;
+    fits.save()?;
+
+    let fits = FsFits::open_mmap(&path)?;
+    let hdu = fits.primary_hdu();
+    let mapped = hdu.mapped_image(1).unwrap();
+    assert_eq!((mapped.width(), mapped.height()), (4, 3));
+    assert_eq!(mapped.as_bytes().len(), 12 * 2);
+
+    let pixels = mapped.pixels::<u16>()?;
+    assert_eq!(pixels.len(), 12);
+    assert_eq!(pixels.get(3), Some(65532));
+    assert_eq!(pixels.get(12), None);
+    assert_eq!(pixels.iter().collect::<Vec<_>>(), second);
+    assert_eq!(mapped.pixels::<i16>()?.get(0), Some(i16::MAX));
+    assert!(mapped.pixels::<f32>().is_err());
+
+    match mapped.to_image()? {
+        Image::U16(image) => assert_eq!(image.raw(), second.as_slice()),
+        _ => panic!("Expected an unsigned 16 bit image"),
+    }
+    match hdu.read_image(0)? {
+        Some(Image::U16(image)) => assert_eq!(image.raw(), first.as_slice()),
+        _ => panic!("Expected an unsigned 16 bit image"),
+    }
+    assert_eq!(hdu.read_raw_data()?.len(), 2 * 12 * 2);
+    assert!(hdu.mapped_image(2).is_none());
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+pub fn open_mmap_should_map_image_extensions()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::temp_dir().join("fits-io-open-mmap-extension.fits");
+    let mut fits = FsFits::from_template(
+        &path,
+        "XTENSION = IMAGE\nBITPIX = -32\nNAXIS = 2\nNAXIS1 = 2\nNAXIS2 = 2",
+    )?;
+    if let Some(ExtensionHDU::Image(hdu)) = fits.extension_hdu_mut(0) {
+        hdu.set_raw_images_f32(2, 2, &[&[0.5, 1.5, 2.5, 3.5]])?;
+    }
+    fits.save()?;
+
+    let fits = FsFits::open_mmap(&path)?;
+    assert!(fits.primary_hdu().mapped_image(0).is_none());
+    let Some(ExtensionHDU::Image(hdu)) = fits.extension_hdu(0) else {
+        panic!("Expected an image extension");
+    };
+    let pixels = hdu.mapped_image(0).unwrap().pixels::<f32>()?;
+    assert_eq!(pixels.iter().collect::<Vec<_>>(), [0.5, 1.5, 2.5, 3.5]);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+pub fn saving_a_clone_should_keep_the_mapping_valid()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::temp_dir().join("fits-io-open-mmap-save.fits");
+    let pixels: Vec<u16> = (0..12).collect();
+    let mut fits = FsFits::new(&path);
+    fits.primary_hdu_mut()
+        .set_raw_images_u16(4, 3, &[&pixels])?;
+    fits.save()?;
+
+    let fits = FsFits::open_mmap(&path)?;
+    let mut clone = fits.clone();
+    clone
+        .primary_hdu_mut()
+        .set_raw_images_u16(2, 1, &[&[7, 8]])?;
+    clone.save()?;
+
+    let mapped = fits.primary_hdu().mapped_image(0).unwrap();
+    assert_eq!(mapped.pixels::<u16>()?.iter().collect::<Vec<_>>(), pixels);
+    match FsFits::open(&path)?.primary_hdu().read_image(0)? {
+        Some(Image::U16(image)) => assert_eq!(image.raw(), [7, 8]),
+        _ => panic!("Expected an unsigned 16 bit image"),
+    }
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+pub fn truncated_mapped_files_should_fail() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let path = std::env::temp_dir().join("fits-io-open-mmap-truncated.fits");
+    let pixels: Vec<u16> = (0..24).collect();
+    let mut fits = FsFits::new(&path);
+    fits.primary_hdu_mut()
+        .set_raw_images_u16(4, 3, &[&pixels[..12], &pixels[12..]])?;
+    let bytes = fits.to_vec()?;
+    std::fs::write(&path, &bytes[..2880 + 30])?;
+
+    let fits = FsFits::open_mmap(&path)?;
+    let hdu = fits.primary_hdu();
+    assert!(hdu.mapped_image(1).is_none());
+    assert!(matches!(
+        hdu.read_region(1, 0, 0, 2, 2),
+        Err(Error::TruncatedData { .. })
+    ));
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
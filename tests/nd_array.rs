@@ -0,0 +1,84 @@
+use fits_io::fs::FsFits;
+use fits_io::hdu::{HDU, ImageHDU};
+use fits_io::image::NdArray;
+use fits_io::{Error, Fits, FitsSlice};
+
+/// A 3 x 2 x 2 x 2 cube of 16 bit values, where every value is its position in the data unit
+fn cube() -> Result<FitsSlice, Box<dyn std::error::Error + Send + Sync>> {
+    let template =
+        "BITPIX = 16\nNAXIS = 4\nNAXIS1 = 3\nNAXIS2 = 2\nNAXIS3 = 2\nNAXIS4 = 2\nBZERO = 100";
+    let mut bytes = FitsSlice::from_template(template)?.to_vec()?;
+    for value in 0..24_i16 {
+        let start = 2880 + value as usize * 2;
+        bytes[start..start + 2].copy_from_slice(&value.to_be_bytes());
+    }
+    Ok(FitsSlice::from_slice(&bytes)?)
+}
+
+#[test]
+pub fn read_slice_should_handle_every_axis() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let fits = cube()?;
+    let hdu = fits.primary_hdu();
+    assert_eq!(hdu.header().data_shape(), [3, 2, 2, 2]);
+    assert_eq!(hdu.image_count(), 4);
+
+    let slice = hdu.read_slice(&[1..3, 0..2, 1..2, 0..2])?;
+    assert_eq!(slice.shape(), [2, 2, 1, 2]);
+    assert_eq!(slice.strides(), [1, 2, 4, 4]);
+    assert_eq!(
+        slice.data(),
+        [107.0, 108.0, 110.0, 111.0, 119.0, 120.0, 122.0, 123.0]
+    );
+    assert_eq!(slice.get(&[1, 0, 0, 1]), Some(&120.0));
+    assert_eq!(slice.get(&[2, 0, 0, 1]), None);
+
+    assert!(hdu.read_slice(&[0..3, 0..2, 0..2]).is_err());
+    assert!(hdu.read_slice(&[0..4, 0..2, 0..2, 0..2]).is_err());
+
+    let last = hdu.read_image(3)?.unwrap();
+    assert_eq!(last.pixel(0, 0), Some(118.0));
+    assert!(hdu.read_image(4)?.is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn nd_array_should_check_its_shape() {
+    assert!(NdArray::from_data(vec![2, 3], vec![0; 6]).is_ok());
+    assert!(NdArray::from_data(vec![2, 3], vec![0; 5]).is_err());
+}
+
+#[test]
+pub fn read_slice_should_handle_hdus_without_data()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let fits = FitsSlice::from_template("SIMPLE = T")?;
+    let hdu = fits.primary_hdu();
+
+    let slice = hdu.read_slice(&[])?;
+    assert!(slice.shape().is_empty());
+    assert!(slice.is_empty());
+    assert!(matches!(
+        hdu.read_slice(&[0..1]),
+        Err(Error::InvalidImage(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn read_slice_should_fail_on_truncated_files()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::temp_dir().join("fits-io-truncated-slice.fits");
+    let bytes = cube()?.to_vec()?;
+    std::fs::write(&path, &bytes[..2880 + 20])?;
+
+    let fits = FsFits::open(&path)?;
+    assert!(matches!(
+        fits.primary_hdu().read_slice(&[0..3, 0..2, 0..2, 1..2]),
+        Err(Error::TruncatedData { .. })
+    ));
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
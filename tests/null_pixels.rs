@@ -0,0 +1,113 @@
+use fits_io::hdu::{HDU, ImageHDU};
+use fits_io::header::Bitpix;
+use fits_io::image::Image;
+use fits_io::{Error, Fits, FitsSlice};
+
+fn reopen(fits: &FitsSlice) -> Result<Image, Box<dyn std::error::Error + Send + Sync>> {
+    let fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    Ok(fits.primary_hdu().read_image(0)?.unwrap())
+}
+
+#[test]
+pub fn blank_and_nan_pixels_should_be_null() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let mut fits = FitsSlice::from_template("BLANK = -32768")?;
+    fits.primary_hdu_mut()
+        .set_raw_images_i16(2, 2, &[&[i16::MIN, 10, 20, 30]])?;
+    let image = reopen(&fits)?;
+
+    assert_eq!(image.mask(), [false, true, true, true]);
+    assert_eq!(image.pixel(0, 0), None);
+    assert_eq!(image.pixel(1, 0), Some(10.0));
+    assert!(image.physical().get_pixel(0, 0)[0].is_nan());
+    assert_eq!(image.data_range(), Some((10.0, 30.0)));
+    assert_eq!(
+        image.valid_pixels().collect::<Vec<_>>(),
+        [(1, 0, 10.0), (0, 1, 20.0), (1, 1, 30.0)]
+    );
+
+    fits.primary_hdu_mut()
+        .set_raw_images_f32(2, 2, &[&[1.0, f32::NAN, 3.0, -32768.0]])?;
+    assert_eq!(reopen(&fits)?.mask(), [true, false, true, true]);
+
+    Ok(())
+}
+
+#[test]
+pub fn nan_should_be_written_as_blank() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FitsSlice::from_template("BSCALE = 0.5")?;
+    fits.primary_hdu_mut()
+        .set_physical_images(Bitpix::I16, 2, 2, &[&[f64::NAN, 1.5, 2.0, 3.0]])?;
+
+    let header = fits.primary_hdu().header();
+    assert_eq!(header.blank(), Some(i16::MIN as i64));
+    assert_eq!(header.bscale(), Some(0.5));
+    let image = reopen(&fits)?;
+    assert_eq!(image.mask(), [false, true, true, true]);
+    assert_eq!(
+        image
+            .valid_pixels()
+            .map(|(_, _, value)| value)
+            .collect::<Vec<_>>(),
+        [1.5, 2.0, 3.0]
+    );
+
+    let mut fits = FitsSlice::from_template("BZERO = 32768")?;
+    fits.primary_hdu_mut()
+        .set_physical_images(Bitpix::I16, 2, 1, &[&[f64::NAN, 65535.0]])?;
+    match reopen(&fits)? {
+        Image::U16(image) => {
+            assert_eq!(image.raw(), [0, 65535]);
+            assert_eq!(image.mask(), [false, true]);
+        }
+        image => panic!("Expected an unsigned 16 bit image, got {:?}", image),
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn blank_should_not_collide_with_pixel_values()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FitsSlice::from_template("")?;
+    fits.primary_hdu_mut()
+        .set_physical_images(Bitpix::U8, 3, 1, &[&[0.0, f64::NAN, 255.0]])?;
+    assert_eq!(fits.primary_hdu().header().blank(), Some(1));
+    let image = reopen(&fits)?;
+    assert_eq!(image.mask(), [true, false, true]);
+    assert_eq!(
+        image
+            .valid_pixels()
+            .map(|(_, _, value)| value)
+            .collect::<Vec<_>>(),
+        [0.0, 255.0]
+    );
+
+    let mut fits = FitsSlice::from_template("BLANK = 1000")?;
+    let result = fits
+        .primary_hdu_mut()
+        .set_physical_images(Bitpix::U8, 2, 1, &[&[0.0, f64::NAN]]);
+    assert!(matches!(result, Err(Error::InvalidValue { keyword, .. }) if keyword == "BLANK"));
+
+    Ok(())
+}
+
+#[test]
+pub fn raw_images_should_only_drop_the_offset_of_the_old_bitpix()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FitsSlice::from_template("BITPIX = 32\nBZERO = 32768")?;
+    fits.primary_hdu_mut()
+        .set_raw_images_i32(2, 1, &[&[-1, 1]])?;
+    assert_eq!(fits.primary_hdu().header().bzero(), Some(32768.0));
+    match reopen(&fits)? {
+        Image::I32(image) => assert_eq!(image.physical().into_raw(), [32767.0, 32769.0]),
+        image => panic!("Expected a 32 bit image, got {:?}", image),
+    }
+
+    let mut fits = FitsSlice::from_template("BITPIX = 16\nBZERO = 32768")?;
+    fits.primary_hdu_mut()
+        .set_raw_images_i32(2, 1, &[&[-1, 1]])?;
+    assert_eq!(fits.primary_hdu().header().bzero(), None);
+
+    Ok(())
+}
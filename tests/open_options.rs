@@ -0,0 +1,141 @@
+use fits_io::hdu::HDU;
+use fits_io::{CardPosition, Error, Fits, OpenOptions, ParseWarning};
+
+fn header(records: &[&str]) -> Vec<u8> {
+    let mut bytes = vec![b' '; 2880];
+    for (index, record) in records.iter().enumerate() {
+        bytes[index * 80..index * 80 + record.len()].copy_from_slice(record.as_bytes());
+    }
+    bytes
+}
+
+fn sloppy_file() -> Vec<u8> {
+    header(&[
+        "SIMPLE  =                    T",
+        "NAXIS   =                    0",
+        "BITPIX  =                    8",
+        "DATE-OBS= '17/11/2025'",
+        "OBJECT  = 'M31'",
+        "END",
+    ])
+}
+
+#[test]
+pub fn lenient_mode_should_collect_warnings() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let data = sloppy_file();
+    let (fits, warnings) = OpenOptions::new().open_slice(&data)?;
+
+    assert_eq!(
+        warnings,
+        vec![
+            ParseWarning {
+                hdu: 0,
+                card: Some(3),
+                keyword: "DATE-OBS".into(),
+                problem: "input contains invalid characters".into(),
+            },
+            ParseWarning {
+                hdu: 0,
+                card: Some(2),
+                keyword: "BITPIX".into(),
+                problem: "Keyword must be card 1 of the header".into(),
+            },
+            ParseWarning {
+                hdu: 0,
+                card: Some(1),
+                keyword: "NAXIS".into(),
+                problem: "Keyword must be card 2 of the header".into(),
+            },
+        ]
+    );
+
+    let header = fits.primary_hdu().header();
+    assert_eq!(header.object(), Some("M31"));
+    assert_eq!(header.date_observed(), None);
+    // The unparsable card keeps its keyword, so it can still be found
+    let date = header.find("DATE-OBS").collect::<Vec<_>>();
+    assert_eq!(date.len(), 1);
+    assert_eq!(date[0].key(), "DATE-OBS");
+    assert_eq!(header.get::<String>("DATE-OBS")?, None);
+    assert_eq!(fits.to_vec()?, data);
+
+    Ok(())
+}
+
+#[test]
+pub fn strict_mode_should_reject_sloppy_files() {
+    let err = OpenOptions::new()
+        .strict(true)
+        .open_slice(&sloppy_file())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid card DATE-OBS at HDU 0, card 3: input contains invalid characters"
+    );
+
+    let missing_simple = header(&[
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "END",
+    ]);
+    assert!(OpenOptions::new().open_slice(&missing_simple).is_err());
+}
+
+#[test]
+pub fn invalid_card_should_report_its_position() {
+    let mut data = header(&[
+        "SIMPLE  =                    T",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "EXTEND  =                    T",
+        "END",
+    ]);
+    data.extend(header(&[
+        "XTENSION= 'IMAGE   '",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "PCOUNT  =                    0",
+        "GCOUNT  =                    1",
+        "DATE-OBS= '17/11/2025'",
+        "END",
+    ]));
+
+    match OpenOptions::new().strict(true).open_slice(&data) {
+        Err(Error::InvalidCard {
+            keyword,
+            position: Some(CardPosition { hdu: 1, card: 5 }),
+            ..
+        }) => assert_eq!(keyword, "DATE-OBS"),
+        other => panic!("unexpected result {other:?}"),
+    }
+}
+
+#[test]
+pub fn warnings_should_count_continue_records()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let data = header(&[
+        "SIMPLE  =                    T",
+        "OBJECT  = 'Andromeda &'",
+        "CONTINUE  'galaxy'",
+        "BITPIX  =                    8",
+        "NAXIS   =                    0",
+        "DATE-OBS= '17/11/2025'",
+        "END",
+    ]);
+    let (_, warnings) = OpenOptions::new().open_slice(&data)?;
+
+    assert_eq!(
+        warnings
+            .iter()
+            .map(|warning| (warning.keyword.as_str(), warning.card))
+            .collect::<Vec<_>>(),
+        [
+            ("DATE-OBS", Some(5)),
+            ("BITPIX", Some(3)),
+            ("NAXIS", Some(4))
+        ]
+    );
+
+    Ok(())
+}
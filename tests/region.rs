@@ -0,0 +1,109 @@
+use fits_io::fs::FsFits;
+use fits_io::hdu::{HDU, ImageHDU};
+use fits_io::header::BayerPattern;
+use fits_io::image::Image;
+use fits_io::{Error, Fits, FitsSlice};
+
+/// Two 6 x 4 planes, where every pixel holds plane * 100 + y * 10 + x
+fn planes() -> Vec<Vec<u16>> {
+    (0..2)
+        .map(|plane| (0..24).map(|i| plane * 100 + i / 6 * 10 + i % 6).collect())
+        .collect()
+}
+
+fn raw(image: Image) -> (u32, u32, Vec<u16>) {
+    match image {
+        Image::U16(image) => (image.width(), image.height(), image.raw().to_vec()),
+        _ => panic!("Expected an unsigned 16 bit image"),
+    }
+}
+
+#[test]
+pub fn read_region_should_only_decode_the_cutout()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::temp_dir().join("fits-io-read-region.fits");
+    let planes = planes();
+    let mut fits = FsFits::new(&path);
+    fits.primary_hdu_mut()
+        .set_raw_images_u16(6, 4, &[&planes[0], &planes[1]])?;
+    fits.primary_hdu_mut()
+        .header_mut()
+        .set_bayer_pattern(BayerPattern::RGGB);
+    fits.save()?;
+
+    let fits = FsFits::open(&path)?;
+    let hdu = fits.primary_hdu();
+    let region = hdu.read_region(1, 1, 2, 3, 2)?.unwrap();
+    assert_eq!(*region.bayer_pattern(), Some(BayerPattern::GRBG));
+    assert_eq!(raw(region), (3, 2, vec![121, 122, 123, 131, 132, 133]));
+
+    let preview = hdu.read_decimated_region(0, 0, 0, 6, 4, 2)?.unwrap();
+    assert_eq!(*preview.bayer_pattern(), None);
+    assert_eq!(raw(preview), (3, 2, vec![0, 2, 4, 20, 22, 24]));
+    assert_eq!(
+        raw(hdu.read_decimated_region(0, 1, 1, 5, 3, 3)?.unwrap()),
+        (2, 1, vec![11, 14])
+    );
+
+    assert!(hdu.read_region(0, 4, 0, 3, 1).is_err());
+    assert!(hdu.read_decimated_region(0, 0, 0, 1, 1, 0).is_err());
+    assert!(hdu.read_region(2, 0, 0, 1, 1)?.is_none());
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn read_region_async_should_match_read_region()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::temp_dir().join("fits-io-read-region-async.fits");
+    let planes = planes();
+    let mut fits = FsFits::new(&path);
+    fits.primary_hdu_mut()
+        .set_raw_images_u16(6, 4, &[&planes[0], &planes[1]])?;
+    fits.save()?;
+
+    let fits = FsFits::open_async(&path).await?;
+    let hdu = fits.primary_hdu();
+    assert_eq!(
+        raw(hdu.read_region_async(0, 4, 3, 2, 1).await?.unwrap()),
+        (2, 1, vec![34, 35])
+    );
+    assert_eq!(
+        raw(hdu
+            .read_decimated_region_async(1, 0, 0, 6, 4, 4)
+            .await?
+            .unwrap()),
+        (2, 1, vec![100, 104])
+    );
+
+    let slice = FitsSlice::from_slice(&fits.to_vec()?)?;
+    assert_eq!(
+        raw(slice.primary_hdu().read_region(1, 5, 0, 1, 4)?.unwrap()),
+        (1, 4, vec![105, 115, 125, 135])
+    );
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+pub fn read_region_should_fail_on_truncated_files()
+-> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::temp_dir().join("fits-io-read-region-truncated.fits");
+    let planes = planes();
+    let mut fits = FitsSlice::from_template("SIMPLE = T")?;
+    fits.primary_hdu_mut()
+        .set_raw_images_u16(6, 4, &[&planes[0], &planes[1]])?;
+    let bytes = fits.to_vec()?;
+    std::fs::write(&path, &bytes[..2880 + 60])?;
+
+    let fits = FsFits::open(&path)?;
+    assert!(matches!(
+        fits.primary_hdu().read_region(1, 0, 0, 2, 2),
+        Err(Error::TruncatedData { .. })
+    ));
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use fits_io::hdu::{BinTableHDU, ExtensionHDU, HDU, ImageHDU};
+use fits_io::header::Bitpix;
+use fits_io::{Error, Fits, FitsSlice};
+
+const TEMPLATE: &str = "# Primary HDU holding a small image
+SIMPLE  = T
+BITPIX  = 16
+NAXIS   = 2
+NAXIS1  = 4 / image width
+NAXIS2  = 3
+EXPTIME = 30 / exposure in seconds
+OBJECT  = 'M42 core'
+COMMENT Created from a template
+
+XTENSION = BINTABLE
+EXTNAME = 'STARS'
+NAXIS2  = 10
+TTYPE#  = 'RA'
+TFORM#  = 1D
+TTYPE#  = 'FLUX'
+TFORM#  = 1E
+\\END
+OBJECT  = 'ignored'
+";
+
+#[test]
+pub fn template_should_create_all_hdus() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut fits = FitsSlice::from_template(TEMPLATE)?;
+
+    let header = fits.primary_hdu().header();
+    assert_eq!(header.bitpix(), Bitpix::I16);
+    assert_eq!(header.naxis_n(0), Some(4));
+    assert_eq!(header.get::<f64>("EXPTIME")?, Some(30.0));
+    assert_eq!(header.get::<String>("OBJECT")?.as_deref(), Some("M42 core"));
+    assert_eq!(
+        header.comments().collect::<Vec<_>>(),
+        ["Created from a template"]
+    );
+    assert_eq!(fits.primary_hdu().read_raw_data()?, vec![0; 24]);
+
+    let Some(ExtensionHDU::BinTable(table)) = fits.extension_hdu(0) else {
+        panic!("expected a binary table");
+    };
+    let header = table.header();
+    assert_eq!(header.naxis_n(0), Some(12));
+    assert_eq!(header.naxis_n(1), Some(10));
+    assert_eq!(header.get::<i64>("TFIELDS")?, Some(2));
+    assert_eq!(header.get::<String>("TTYPE2")?.as_deref(), Some("FLUX"));
+    assert_eq!(table.read_table()?.len(), 10);
+
+    let pixels: Vec<i16> = (0..12).collect();
+    fits.primary_hdu_mut()
+        .set_raw_images_i16(4, 3, &[&pixels])?;
+
+    let fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    assert_eq!(fits.extension_hdus().count(), 1);
+    assert_eq!(
+        fits.primary_hdu().header().get::<f64>("EXPTIME")?,
+        Some(30.0)
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn invalid_template_lines_should_be_reported() {
+    let result = FitsSlice::from_template("SIMPLE = T\nOBJECT = 'M42\n");
+    assert!(matches!(
+        result,
+        Err(Error::InvalidTemplate { line: 2, .. })
+    ));
+
+    let result = FitsSlice::from_template("NAXIS = 1\nXTENSION = TABLE\nNAXIS2 = 4\n");
+    assert!(matches!(
+        result,
+        Err(Error::InvalidTemplate { line: 2, .. })
+    ));
+}
+
+#[test]
+pub fn template_naxis_should_be_kept() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let fits = FitsSlice::from_template("NAXIS = 2")?;
+    let header = fits.primary_hdu().header();
+    assert_eq!(header.get::<i64>("NAXIS")?, Some(2));
+    assert_eq!(header.naxis_n(0), Some(0));
+    assert_eq!(header.naxis_n(1), Some(0));
+
+    let fits = FitsSlice::from_template("NAXIS = 3\nNAXIS1 = 4\nNAXIS2 = 2")?;
+    let header = fits.primary_hdu().header();
+    assert_eq!(header.get::<i64>("NAXIS")?, Some(3));
+    assert_eq!(header.naxis_n(2), Some(0));
+
+    let result = FitsSlice::from_template("NAXIS = 1\nNAXIS1 = 4\nNAXIS2 = 2");
+    assert!(matches!(
+        result,
+        Err(Error::InvalidTemplate { line: 1, .. })
+    ));
+
+    Ok(())
+}
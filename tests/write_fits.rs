@@ -0,0 +1,138 @@
+use fits_io::fs::FsFits;
+use fits_io::hdu::{HDU, ImageHDU};
+use fits_io::header::Bitpix;
+use fits_io::image::Image;
+use fits_io::{Error, Fits, FitsSlice};
+use image::{ImageBuffer, Luma};
+
+#[test]
+pub fn save_should_round_trip() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::temp_dir().join("fits-io-save-should-round-trip.fits");
+    let pixels: Vec<i16> = (0..6 * 4).map(|i| i * 100 - 1000).collect();
+
+    let mut fits = FsFits::new(&path);
+    fits.primary_hdu_mut()
+        .set_raw_images_i16(6, 4, &[&pixels])?;
+    fits.save()?;
+
+    let written = std::fs::read(&path)?;
+    assert_eq!(written.len() % 2880, 0);
+
+    let fits = FsFits::open(&path)?;
+    assert_eq!(fits.primary_hdu().images_width(), 6);
+    assert_eq!(fits.primary_hdu().images_height(), 4);
+
+    let image = fits.primary_hdu().read_image(0)?.unwrap();
+    if let fits_io::image::Image::I16(image) = image {
+        assert_eq!(image.raw(), pixels.as_slice());
+    } else {
+        panic!("Expected a 16 bit image");
+    }
+
+    assert_eq!(fits.to_vec()?, written);
+    assert_eq!(FitsSlice::from_slice(&written)?.to_vec()?, written);
+    assert_eq!(fits.primary_hdu().read_raw_data()?.len(), 6 * 4 * 2);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+pub fn i64_images_should_round_trip() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let pixels: Vec<i64> = (0..4 * 3).map(|i| i64::MAX - i * 1_000_000_007).collect();
+
+    let mut fits = FitsSlice::from_template("SIMPLE = T")?;
+    fits.primary_hdu_mut()
+        .set_raw_images_i64(4, 3, &[&pixels])?;
+
+    let fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    assert_eq!(fits.primary_hdu().header().bitpix(), Bitpix::I64);
+    let image = fits.primary_hdu().read_image(0)?.unwrap();
+    if let fits_io::image::Image::I64(image) = image {
+        assert_eq!(image.raw(), pixels.as_slice());
+    } else {
+        panic!("Expected a 64 bit image");
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn unsigned_images_should_use_bzero() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let pixels: Vec<u16> = vec![0, 1, 32767, 32768, 40000, u16::MAX];
+
+    let mut fits = FitsSlice::from_template("SIMPLE = T")?;
+    fits.primary_hdu_mut()
+        .set_raw_images_u16(3, 2, &[&pixels])?;
+    assert_eq!(
+        fits.primary_hdu().read_raw_data()?[..4],
+        [0x80, 0x00, 0x80, 0x01]
+    );
+
+    let mut fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    let header = fits.primary_hdu().header();
+    assert_eq!(header.bitpix(), Bitpix::I16);
+    assert_eq!(header.bzero(), Some(32768.0));
+    assert_eq!(header.bscale(), Some(1.0));
+    match fits.primary_hdu().read_image(0)?.unwrap() {
+        Image::U16(image) => assert_eq!(image.raw(), pixels.as_slice()),
+        image => panic!("Expected an unsigned 16 bit image, got {:?}", image),
+    }
+
+    let pixels: Vec<u64> = vec![0, u64::MAX, 1 << 63, 12345];
+    fits.primary_hdu_mut()
+        .set_raw_images_u64(2, 2, &[&pixels])?;
+    let mut fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    match fits.primary_hdu().read_image(0)?.unwrap() {
+        Image::U64(image) => assert_eq!(image.raw(), pixels.as_slice()),
+        image => panic!("Expected an unsigned 64 bit image, got {:?}", image),
+    }
+
+    let pixels: Vec<i8> = vec![i8::MIN, -1, 0, i8::MAX];
+    fits.primary_hdu_mut().set_raw_images_i8(2, 2, &[&pixels])?;
+    let mut fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    assert_eq!(fits.primary_hdu().header().bzero(), Some(-128.0));
+    match fits.primary_hdu().read_image(0)?.unwrap() {
+        Image::I8(image) => assert_eq!(image.raw(), pixels.as_slice()),
+        image => panic!("Expected a signed 8 bit image, got {:?}", image),
+    }
+
+    fits.primary_hdu_mut()
+        .set_raw_images_i16(2, 2, &[&[-1, 0, 1, 2]])?;
+    assert_eq!(fits.primary_hdu().header().bzero(), None);
+    assert!(matches!(
+        fits.primary_hdu().read_image(0)?.unwrap(),
+        Image::I16(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn image_buffers_should_keep_their_size() -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let pixels: Vec<u16> = vec![0, 1, 2, 40000, 50000, 60000];
+    let image = ImageBuffer::<Luma<u16>, _>::from_raw(3, 2, pixels.clone()).unwrap();
+
+    let mut fits = FitsSlice::from_template("SIMPLE = T")?;
+    fits.primary_hdu_mut().set_images_u16(&[&image, &image])?;
+    let fits = FitsSlice::from_slice(&fits.to_vec()?)?;
+    let header = fits.primary_hdu().header();
+    assert_eq!(
+        (header.naxis_n(0), header.naxis_n(1), header.naxis_n(2)),
+        (Some(3), Some(2), Some(2))
+    );
+    match fits.primary_hdu().read_image(1)?.unwrap() {
+        Image::U16(image) => assert_eq!(image.raw(), pixels.as_slice()),
+        image => panic!("Expected an unsigned 16 bit image, got {:?}", image),
+    }
+
+    let other = ImageBuffer::<Luma<u16>, _>::new(2, 3);
+    let mut fits = FitsSlice::from_template("SIMPLE = T")?;
+    assert!(matches!(
+        fits.primary_hdu_mut().set_images_u16(&[&image, &other]),
+        Err(Error::InvalidImage(_))
+    ));
+
+    Ok(())
+}